@@ -0,0 +1,46 @@
+use std::io;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use interpreter::*;
+
+/// Examples that don't read any input
+const EXAMPLES: [(&str, &str); 3] = [
+    ("fizzbuzz", include_str!("../../examples/fizzbuzz.factory")),
+    (
+        "fibonacci",
+        include_str!("../../examples/fibonacci.factory"),
+    ),
+    (
+        "hello_world_pretty",
+        include_str!("../../examples/hello_world_pretty.factory"),
+    ),
+];
+
+/// Compares the step-by-step runtime with the compiled IR on each example
+fn bench_examples(c: &mut Criterion) {
+    let config = Config::default();
+    for (name, src) in EXAMPLES {
+        let program = Program::new(src).unwrap();
+        let mut group = c.benchmark_group(name);
+        group.bench_function("runtime", |b| {
+            let mut interpreter = Interpreter::new().stdout(io::sink());
+            b.iter(|| {
+                runtime::execute(
+                    program.stations().clone(),
+                    &mut interpreter.context(),
+                    &mut (),
+                )
+                .unwrap()
+            });
+        });
+        group.bench_function("ir", |b| {
+            let ir = program.compile(&config);
+            let mut interpreter = Interpreter::new().stdout(io::sink());
+            b.iter(|| ir.execute(&mut interpreter.context()).unwrap());
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_examples);
+criterion_main!(benches);
@@ -0,0 +1,414 @@
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossterm::{
+    event::{self, Event as TermEvent, KeyCode, KeyEventKind, KeyModifiers},
+    terminal,
+};
+
+use crate::*;
+
+/// Number of lines of program output shown under the source
+const OUTPUT_LINES: usize = 8;
+/// Longest pallet value drawn on a belt, in characters
+const MAX_LABEL_LEN: usize = 8;
+
+/// Observer that redraws the source code every step, showing pallets travelling
+/// along their conveyor belts and highlighting the stations that fired
+pub struct Animator<W: Write> {
+    /// 2d character layout of the source code
+    grid: Vec<Vec<char>>,
+    out: W,
+    /// Steps per second, zero draws every frame without waiting
+    speed: f64,
+    color: bool,
+    /// Read pause and speed controls from the terminal
+    interactive: bool,
+    paused: bool,
+    step: usize,
+    /// Belt paths and labels of the pallets moving this step, yet to be drawn
+    travelling: Vec<(Vec<SourcePos>, String)>,
+    /// Locations of the stations that fired this step
+    fired: Vec<SourceSpan>,
+    /// Output of the program, shown under the source
+    output: SharedBuffer,
+    /// First error encountered while drawing, later frames are skipped
+    error: Option<io::Error>,
+    /// Whether the user quit, the run stops after the current step
+    quit: bool,
+}
+impl<W: Write> Animator<W> {
+    /// Creates an animator for a program's source code, drawing to an output
+    /// stream
+    pub fn new(src: &str, out: W) -> Self {
+        return Self {
+            grid: src.split('\n').map(|line| line.chars().collect()).collect(),
+            out,
+            speed: 2.0,
+            color: false,
+            interactive: false,
+            paused: false,
+            step: 0,
+            travelling: Vec::new(),
+            fired: Vec::new(),
+            output: SharedBuffer::default(),
+            error: None,
+            quit: false,
+        };
+    }
+
+    /// Sets the number of steps animated per second, zero doesn't wait between
+    /// frames
+    pub fn with_speed(self, speed: f64) -> Self {
+        return Self { speed, ..self };
+    }
+
+    /// Sets whether to highlight with ANSI escape codes
+    pub fn with_color(self, color: bool) -> Self {
+        return Self { color, ..self };
+    }
+
+    /// Sets whether to read keyboard controls from the terminal: space pauses,
+    /// `n` steps while paused, `+` and `-` change speed, and `q` quits
+    pub fn with_interactive(self, interactive: bool) -> Self {
+        return Self {
+            interactive,
+            ..self
+        };
+    }
+
+    /// Stream to use as the program's output, so it is drawn under the source
+    /// instead of being cleared by the next frame
+    pub fn output(&self) -> SharedBuffer {
+        return self.output.clone();
+    }
+
+    /// Whether the user quit the animation with `q` or Ctrl-C, in which case the
+    /// run was stopped early and the terminal may need a newline
+    pub fn quit_requested(&self) -> bool {
+        return self.quit;
+    }
+
+    /// Returns the first error encountered while drawing
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        self.out.flush()?;
+        return Ok(self.out);
+    }
+
+    /// Draws the pallets moving this step along their belts
+    fn draw_travel(&mut self) {
+        let travelling = std::mem::take(&mut self.travelling);
+        let frames = travelling
+            .iter()
+            .map(|(path, _)| path.len())
+            .max()
+            .unwrap_or(0);
+        for frame in 0..frames {
+            // every pallet arrives at the same time, no matter the belt length
+            let pallets: Vec<(SourcePos, &str)> = travelling
+                .iter()
+                .filter(|(path, _)| !path.is_empty())
+                .map(|(path, label)| (path[frame * path.len() / frames], label.as_str()))
+                .collect();
+            self.draw(&pallets);
+            self.wait(self.step_duration() / (2 * frames as u32));
+        }
+    }
+
+    /// Draws a frame with the stations that fired this step
+    fn draw_fired(&mut self) {
+        self.draw(&[]);
+        self.wait(self.step_duration() / 2);
+    }
+
+    fn step_duration(&self) -> Duration {
+        if self.speed <= 0.0 {
+            return Duration::ZERO;
+        }
+        return Duration::from_secs_f64(1.0 / self.speed);
+    }
+
+    /// Clears the terminal and draws the source, with pallets drawn over it
+    fn draw(&mut self, pallets: &[(SourcePos, &str)]) {
+        if self.error.is_some() || self.quit {
+            return;
+        }
+        let frame = self.render(pallets);
+        let result = write!(self.out, "\x1b[H\x1b[2J{frame}").and_then(|_| self.out.flush());
+        if let Err(e) = result {
+            self.error = Some(e);
+        }
+    }
+
+    /// Renders a frame as text, lines end with "\r\n" so frames are drawn
+    /// correctly while the terminal is in raw mode
+    fn render(&self, pallets: &[(SourcePos, &str)]) -> String {
+        // each cell is a character and whether it is highlighted as a fired
+        // station or a pallet
+        let mut cells: Vec<Vec<(char, Highlight)>> = self
+            .grid
+            .iter()
+            .map(|line| line.iter().map(|c| (*c, Highlight::None)).collect())
+            .collect();
+        for span in self.fired.iter() {
+            for col in span.pos.col..(span.pos.col + span.len) {
+                cells[span.pos.line][col].1 = Highlight::Fired;
+            }
+        }
+        for (pos, label) in pallets {
+            let line = &mut cells[pos.line];
+            for (i, c) in label.chars().enumerate() {
+                let col = pos.col + i;
+                while line.len() <= col {
+                    line.push((' ', Highlight::None));
+                }
+                line[col] = (c, Highlight::Pallet);
+            }
+        }
+
+        let mut frame = format!("step {}  ({} steps/s)", self.step, self.speed);
+        if self.paused {
+            frame.push_str("  PAUSED");
+        }
+        frame.push_str("\r\n");
+        for line in cells {
+            let mut current = Highlight::None;
+            for (c, highlight) in line {
+                if self.color && highlight != current {
+                    frame.push_str(highlight.escape_code());
+                    current = highlight;
+                }
+                frame.push(c);
+            }
+            if self.color && current != Highlight::None {
+                frame.push_str(Highlight::None.escape_code());
+            }
+            frame.push_str("\r\n");
+        }
+        if self.interactive {
+            frame.push_str("[space] pause  [n] step  [+/-] speed  [q] quit\r\n");
+        }
+
+        let output = self.output.contents();
+        let lines: Vec<&str> = output.lines().collect();
+        frame.push_str("──────── output ────────\r\n");
+        for line in &lines[lines.len().saturating_sub(OUTPUT_LINES)..] {
+            frame.push_str(line);
+            frame.push_str("\r\n");
+        }
+        return frame;
+    }
+
+    /// Waits between frames, handling keyboard controls if interactive
+    fn wait(&mut self, duration: Duration) {
+        if self.quit {
+            return;
+        }
+        if !self.interactive {
+            thread::sleep(duration);
+            return;
+        }
+        if terminal::enable_raw_mode().is_err() {
+            self.interactive = false;
+            thread::sleep(duration);
+            return;
+        }
+        let start = Instant::now();
+        while self.paused || start.elapsed() < duration {
+            let timeout = if self.paused {
+                Duration::from_millis(100)
+            } else {
+                duration.saturating_sub(start.elapsed())
+            };
+            let Ok(true) = event::poll(timeout) else {
+                continue;
+            };
+            let Ok(TermEvent::Key(key)) = event::read() else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char(' ') => self.paused = !self.paused,
+                KeyCode::Char('n') if self.paused => break,
+                KeyCode::Char('+') | KeyCode::Char('=') => {
+                    self.speed = (self.speed * 2.0).min(64.0)
+                }
+                KeyCode::Char('-') => self.speed = (self.speed / 2.0).max(0.25),
+                KeyCode::Char('q') => {
+                    self.quit = true;
+                    break;
+                }
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.quit = true;
+                    break;
+                }
+                _ => continue,
+            }
+            // redrawing the header
+            let frame = self.render(&[]);
+            let _ = write!(self.out, "\x1b[H{}\r\n", frame.lines().next().unwrap_or(""));
+            let _ = self.out.flush();
+        }
+        let _ = terminal::disable_raw_mode();
+    }
+}
+impl<W: Write> Observer for Animator<W> {
+    fn on_event(&mut self, event: &Event, stations: &[Station]) {
+        match *event {
+            Event::StepStart { step } => {
+                self.step = step;
+                self.travelling.clear();
+                self.fired.clear();
+            }
+            Event::PalletMoved {
+                from,
+                to,
+                bay,
+                pallet,
+            } => {
+                let path = stations[from]
+                    .out_bays
+                    .iter()
+                    .find(|out_bay| out_bay.dest == (to, bay))
+                    .map(|out_bay| out_bay.path.clone())
+                    .unwrap_or_default();
+                self.travelling.push((path, label(pallet)));
+            }
+            Event::StationTriggered { station } => {
+                if !self.travelling.is_empty() {
+                    self.draw_travel();
+                }
+                self.fired.push(stations[station].loc);
+            }
+            Event::StepEnd { .. } | Event::Halted { .. } => {
+                if !self.travelling.is_empty() {
+                    self.draw_travel();
+                }
+                self.draw_fired();
+            }
+            _ => {}
+        }
+    }
+    fn stop_requested(&self) -> bool {
+        return self.quit;
+    }
+}
+
+/// How a cell is highlighted
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Highlight {
+    None,
+    Fired,
+    Pallet,
+}
+impl Highlight {
+    fn escape_code(&self) -> &'static str {
+        match self {
+            Highlight::None => "\x1b[0m",
+            Highlight::Fired => "\x1b[1;30;43m",
+            Highlight::Pallet => "\x1b[1;97;44m",
+        }
+    }
+}
+
+/// Short form of a pallet's value, drawn on the belts
+fn label(pallet: &Pallet) -> String {
+    let label = match pallet {
+        Pallet::Empty => String::from("●"),
+        Pallet::Bool(b) => b.to_string(),
+        Pallet::Char(c) => format!("'{c}'"),
+        Pallet::String(s) => format!("\"{s}\""),
+        Pallet::Int(i) => i.to_string(),
+        #[cfg(feature = "bigint")]
+        Pallet::BigInt(i) => i.to_string(),
+        Pallet::Float(f) => f.to_string(),
+        Pallet::List(_) | Pallet::Map(_) => pallet.literal(),
+    };
+    if label.chars().count() > MAX_LABEL_LEN {
+        let mut short: String = label.chars().take(MAX_LABEL_LEN - 1).collect();
+        short.push('…');
+        return short;
+    }
+    return label;
+}
+
+/// Output stream shared between the interpreter and an animator, storing
+/// everything written to it
+#[derive(Debug, Clone, Default)]
+pub struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+impl SharedBuffer {
+    /// Everything written so far, as text
+    pub fn contents(&self) -> String {
+        return String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned();
+    }
+}
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        return Ok(buf.len());
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_label() {
+        assert_eq!(label(&Pallet::Int(-3)), "-3");
+        assert_eq!(label(&Pallet::String("hi".into())), "\"hi\"");
+        assert_eq!(label(&Pallet::String("hello world".into())), "\"hello …");
+    }
+
+    #[test]
+    fn test_animate() {
+        let src = "[start]═─{\"hi\"}═─[println]";
+        let program = Program::new(src).unwrap();
+        let mut animator = Animator::new(src, Vec::new()).with_speed(0.0);
+        Interpreter::new()
+            .stdout(animator.output())
+            .run_observed(&program, &mut animator)
+            .unwrap();
+        let out = String::from_utf8(animator.finish().unwrap()).unwrap();
+        let frames: Vec<&str> = out.split("\x1b[H\x1b[2J").skip(1).collect();
+
+        // the empty pallet travels from start to the assign station, then the
+        // string pallet travels to the println station
+        assert!(frames[0].starts_with("step 0"));
+        assert!(frames[0].contains("[start]●─{\"hi\"}═─[println]"));
+        assert!(frames.iter().any(|f| f.contains("{\"hi\"}\"hi\"rintln]")));
+        assert!(frames
+            .last()
+            .unwrap()
+            .ends_with("output ────────\r\nhi\r\n"));
+    }
+
+    #[test]
+    fn test_animate_quit() {
+        let src = "[start]═─{\"hi\"}═─[println]";
+        let program = Program::new(src).unwrap();
+        let mut animator = Animator::new(src, Vec::new()).with_speed(0.0);
+        animator.quit = true;
+        let output = animator.output();
+        let report = Interpreter::new()
+            .stdout(output.clone())
+            .run_observed(&program, &mut animator)
+            .unwrap();
+        assert!(animator.quit_requested());
+        assert_eq!(report.steps, 1);
+        assert_eq!(output.contents(), "");
+        assert!(animator.finish().unwrap().is_empty());
+    }
+}
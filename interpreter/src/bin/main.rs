@@ -1,64 +1,543 @@
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
 use std::fs::File;
-use std::io::prelude::*;
+use std::io::{self, prelude::*, BufWriter, IsTerminal};
 use std::process::ExitCode;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 
 use interpreter::*;
 
 fn main() -> ExitCode {
     let cli = Cli::parse();
 
-    // setting global options
-    unsafe {
-        DEBUG_LEVEL = cli.debug_level;
-        COLOR_OUTPUT = !cli.no_color;
-        debug!(1, "Debug level:\t{}", DEBUG_LEVEL);
+    let mut config = Config::default()
+        .with_debug_level(cli.debug_level)
+        .with_logger(TerminalLogger {
+            color: !cli.no_color,
+        })
+        .with_physical_belts(cli.physical_belts)
+        .with_parallel(cli.parallel)
+        .with_optimize(!cli.no_optimize);
+    if let Some(seed) = cli.seed {
+        config = config.with_seed(seed);
+    }
+    debug!(config, 1, "Debug level:\t{}", cli.debug_level);
+    // resumed runs use the seed from their snapshot
+    if !matches!(cli.command, Some(Command::Resume { .. })) {
+        debug!(config, 1, "Seed:\t\t{}", config.seed);
     }
 
-    // reading file
-    let file_name: String = match cli.file {
+    match cli.command {
+        Some(Command::Run(args)) => run_file(&args, &config, !cli.no_color),
+        Some(Command::Debug { file }) => debug_file(&file, &config),
+        Some(Command::Build { file, output }) => build_file(&file, output.as_deref(), &config),
+        Some(Command::Resume {
+            snapshot,
+            snapshot_at,
+        }) => resume_file(&snapshot, snapshot_at, cli.seed, &config),
+        Some(Command::Coverage {
+            file,
+            coverage_file,
+            html,
+        }) => coverage_report(
+            &file,
+            &coverage_file,
+            html.as_deref(),
+            &config,
+            !cli.no_color,
+        ),
+        None => run_file(&cli.run, &config, !cli.no_color),
+    }
+}
+
+/// Preprocesses and runs a program from a file
+fn run_file(args: &RunArgs, config: &Config, color: bool) -> ExitCode {
+    let file_name = match &args.file {
         Some(s) => s,
         None => {
-            print_err!("No file provided");
+            print_err!(config, "No file provided");
             return ExitCode::FAILURE;
         }
     };
-    debug!(1, "Input file:\t{}", file_name);
-    let mut file = match File::open(&file_name) {
-        Ok(f) => f,
+    let Some((file_contents, program)) = load_program(file_name, config) else {
+        return ExitCode::FAILURE;
+    };
+    if args.dump_ir {
+        print!("{}", program.compile(config));
+        return ExitCode::SUCCESS;
+    }
+    let mut interpreter = Interpreter::new().config(config.clone());
+    let mut observers: Vec<&mut dyn Observer> = Vec::new();
+
+    let mut trace = match &args.trace {
+        Some(trace_file) => match File::create(trace_file) {
+            Ok(f) => Some(TraceWriter::new(BufWriter::new(f))),
+            Err(e) => {
+                print_err!(config, "Failed to create file \"{}\": {}", trace_file, e);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => None,
+    };
+    if let Some(trace) = trace.as_mut() {
+        observers.push(trace);
+    }
+    let mut animator = if args.animate {
+        let animator = Animator::new(&file_contents, io::stdout())
+            .with_speed(args.speed)
+            .with_color(color)
+            .with_interactive(io::stdin().is_terminal());
+        interpreter = interpreter.stdout(animator.output());
+        Some(animator)
+    } else {
+        None
+    };
+    if let Some(animator) = animator.as_mut() {
+        observers.push(animator);
+    }
+
+    let mut coverage = match &args.coverage {
+        Some(coverage_file) => match Coverage::load(coverage_file) {
+            Ok(c) => Some(c),
+            Err(e) => {
+                print_err!(
+                    config,
+                    "Failed to read coverage \"{}\": {}",
+                    coverage_file,
+                    e
+                );
+                return ExitCode::FAILURE;
+            }
+        },
+        None => None,
+    };
+    if let Some(coverage) = coverage.as_mut() {
+        observers.push(coverage);
+    }
+    let replay = match &args.replay {
+        Some(session_file) => match Session::load(session_file) {
+            Ok(s) => Some(s),
+            Err(e) => {
+                print_err!(config, "Failed to read session \"{}\": {}", session_file, e);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => None,
+    };
+    if let Some(replay) = &replay {
+        interpreter = interpreter.stdin(io::Cursor::new(replay.stdin()));
+    }
+    let mut session = if args.record.is_some() || replay.is_some() {
+        Some(Session::new())
+    } else {
+        None
+    };
+    if let Some(session) = session.as_mut() {
+        interpreter = interpreter.record_stdin(session);
+        observers.push(session);
+    }
+    let mut profiler = Profiler::new();
+    if args.profile {
+        observers.push(&mut profiler);
+    }
+
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let result = if args.snapshot.is_some() {
+        let mut stop = stop_for_snapshot(args.snapshot_at, &interrupted, config);
+        interpreter.run_until(&program, None, &mut observers, &mut stop)
+    } else if observers.is_empty() {
+        interpreter.run(&program).map(|report| (report, None))
+    } else {
+        interpreter
+            .run_observed(&program, &mut observers)
+            .map(|report| (report, None))
+    };
+    drop(observers);
+
+    if args.profile {
+        println!(
+            "\n{}\n{}",
+            profiler.annotated_source(&file_contents, program.stations(), color),
+            profiler.table(program.stations(), args.profile_sort)
+        );
+    }
+
+    if let (Some(trace), Some(trace_file)) = (trace, &args.trace) {
+        if let Err(e) = trace.finish() {
+            print_err!(config, "Failed to write trace \"{}\": {}", trace_file, e);
+            return ExitCode::FAILURE;
+        }
+    }
+    let mut quit = false;
+    if let Some(animator) = animator {
+        quit = animator.quit_requested();
+        let _ = animator.finish();
+        if quit {
+            // quitting can leave the terminal in raw mode mid frame
+            let _ = crossterm::terminal::disable_raw_mode();
+            println!();
+            interrupted.store(true, Ordering::SeqCst);
+        }
+    }
+    if let (Some(coverage), Some(coverage_file)) = (coverage, &args.coverage) {
+        if let Err(e) = coverage.save(coverage_file) {
+            print_err!(
+                config,
+                "Failed to write coverage \"{}\": {}",
+                coverage_file,
+                e
+            );
+            return ExitCode::FAILURE;
+        }
+    }
+
+    if let (Some(session), Some(session_file)) = (&session, &args.record) {
+        if let Err(e) = session.save(session_file) {
+            print_err!(
+                config,
+                "Failed to write session \"{}\": {}",
+                session_file,
+                e
+            );
+            return ExitCode::FAILURE;
+        }
+    }
+    if let (Some(session), Some(replay), Ok(_)) = (&session, &replay, &result) {
+        if let Some(i) = replay.divergence(session) {
+            print_err!(
+                config,
+                "Replay diverged from \"{}\" at input {}",
+                args.replay.as_deref().unwrap_or_default(),
+                i + 1
+            );
+            return ExitCode::FAILURE;
+        }
+    }
+
+    match result {
+        Ok((_, Some(runtime))) => {
+            // resuming can happen from another directory
+            let source = std::fs::canonicalize(file_name)
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|_| file_name.clone());
+            let snapshot = Snapshot::capture(&runtime, &source, &file_contents, config);
+            let snapshot_file = args.snapshot.as_deref().unwrap_or_default();
+            save_snapshot(&snapshot, snapshot_file, &interrupted, config)
+        }
+        Ok(_) if quit => ExitCode::from(130),
+        Ok((report, None)) => {
+            if args.benchmark {
+                println!("\n{report}");
+            }
+            ExitCode::SUCCESS
+        }
         Err(e) => {
-            print_err!("Failed to open file \"{}\": {}", file_name, e);
+            print_err!(config, "{}", e.pretty_msg(&file_contents));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Continues a program from a snapshot, checking its source file hasn't
+/// changed. If it's stopped again the snapshot is overwritten. Random stations
+/// keep the snapshot's seed unless another one is given
+fn resume_file(
+    snapshot_file: &str,
+    snapshot_at: Option<usize>,
+    seed: Option<u64>,
+    config: &Config,
+) -> ExitCode {
+    let snapshot = match Snapshot::load(snapshot_file) {
+        Ok(s) => s,
+        Err(e) => {
+            print_err!(
+                config,
+                "Failed to read snapshot \"{}\": {}",
+                snapshot_file,
+                e
+            );
             return ExitCode::FAILURE;
         }
     };
-    debug!(2, "Opened file");
-    let mut file_contents = String::new();
-    let bytes_read = match file.read_to_string(&mut file_contents) {
-        Ok(b) => b,
+    let config = &config
+        .clone()
+        .with_physical_belts(snapshot.physical_belts)
+        .with_seed(seed.unwrap_or(snapshot.seed));
+    debug!(config, 1, "Seed:\t\t{}", config.seed);
+    let Some((file_contents, program)) = load_program(&snapshot.source, config) else {
+        return ExitCode::FAILURE;
+    };
+    let runtime = match snapshot.restore(&program, &file_contents) {
+        Ok(r) => r,
         Err(e) => {
-            print_err!("Failed to read file \"{}\": {}", file_name, e);
+            print_err!(config, "Can't resume \"{}\": {}", snapshot_file, e);
             return ExitCode::FAILURE;
         }
     };
-    debug!(2, "Read {} bytes", bytes_read);
-    debug!(
-        4,
-        "Contents --------------\n{}\n-----------------------", file_contents
+    debug!(config, 1, "Resuming at step {}", snapshot.step_count);
+
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let mut stop = stop_for_snapshot(snapshot_at, &interrupted, config);
+    let mut interpreter = Interpreter::new().config(config.clone());
+    match interpreter.run_until(&program, Some(runtime), &mut (), &mut stop) {
+        Ok((_, Some(runtime))) => {
+            let snapshot = Snapshot::capture(&runtime, &snapshot.source, &file_contents, config);
+            return save_snapshot(&snapshot, snapshot_file, &interrupted, config);
+        }
+        Ok((_, None)) => return ExitCode::SUCCESS,
+        Err(e) => {
+            print_err!(config, "{}", e.pretty_msg(&file_contents));
+            return ExitCode::FAILURE;
+        }
+    }
+}
+
+/// Condition for stopping a run to take a snapshot: after a number of steps, or
+/// once the current step finishes after Ctrl-C is pressed
+fn stop_for_snapshot(
+    snapshot_at: Option<usize>,
+    interrupted: &Arc<AtomicBool>,
+    config: &Config,
+) -> impl FnMut(&Runtime) -> bool {
+    let handler_flag = interrupted.clone();
+    if let Err(e) = ctrlc::set_handler(move || handler_flag.store(true, Ordering::SeqCst)) {
+        print_err!(config, "Failed to handle Ctrl-C: {}", e);
+    }
+    let interrupted = interrupted.clone();
+    return move |runtime: &Runtime| {
+        return interrupted.load(Ordering::SeqCst)
+            || snapshot_at.is_some_and(|steps| runtime.step_count >= steps);
+    };
+}
+
+/// Writes a snapshot of a stopped run, exiting like the process was
+/// interrupted if it was stopped by Ctrl-C
+fn save_snapshot(
+    snapshot: &Snapshot,
+    snapshot_file: &str,
+    interrupted: &AtomicBool,
+    config: &Config,
+) -> ExitCode {
+    if let Err(e) = snapshot.save(snapshot_file) {
+        print_err!(
+            config,
+            "Failed to write snapshot \"{}\": {}",
+            snapshot_file,
+            e
+        );
+        return ExitCode::FAILURE;
+    }
+    eprintln!(
+        "Saved snapshot \"{}\" at step {}",
+        snapshot_file, snapshot.step_count
     );
+    if interrupted.load(Ordering::SeqCst) {
+        return ExitCode::from(130);
+    }
+    return ExitCode::SUCCESS;
+}
 
-    match run(&file_contents, cli.benchmark) {
-        Ok(()) => ExitCode::SUCCESS,
+/// Preprocesses a program from a file and starts an interactive debugger
+fn debug_file(file_name: &str, config: &Config) -> ExitCode {
+    let Some((_, program)) = load_program(file_name, config) else {
+        return ExitCode::FAILURE;
+    };
+    let mut interpreter = Interpreter::new().config(config.clone());
+    if let Err(e) = Debugger::new(&program).run(&mut interpreter) {
+        print_err!(config, "{}", e);
+        return ExitCode::FAILURE;
+    }
+    return ExitCode::SUCCESS;
+}
+
+/// Transpiles a program to Rust, written to a file or printed
+fn build_file(file_name: &str, output: Option<&str>, config: &Config) -> ExitCode {
+    let Some((file_contents, program)) = load_program(file_name, config) else {
+        return ExitCode::FAILURE;
+    };
+    let rust_src = match transpiler::transpile(&program, file_name, config) {
+        Ok(s) => s,
         Err(e) => {
-            print_err!("{}", e.pretty_msg(&file_contents));
-            ExitCode::FAILURE
+            print_err!(config, "{}", e.pretty_msg(&file_contents));
+            return ExitCode::FAILURE;
+        }
+    };
+    match output {
+        Some(output_file) => {
+            if let Err(e) = std::fs::write(output_file, rust_src) {
+                print_err!(config, "Failed to write file \"{}\": {}", output_file, e);
+                return ExitCode::FAILURE;
+            }
+        }
+        None => print!("{rust_src}"),
+    }
+    return ExitCode::SUCCESS;
+}
+
+/// Prints a coverage report for a program, or writes it as HTML
+fn coverage_report(
+    file_name: &str,
+    coverage_file: &str,
+    html: Option<&str>,
+    config: &Config,
+    color: bool,
+) -> ExitCode {
+    let Some((file_contents, program)) = load_program(file_name, config) else {
+        return ExitCode::FAILURE;
+    };
+    let coverage = match Coverage::load(coverage_file) {
+        Ok(c) => c,
+        Err(e) => {
+            print_err!(
+                config,
+                "Failed to read coverage \"{}\": {}",
+                coverage_file,
+                e
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+    match html {
+        Some(html_file) => {
+            let report = coverage.html_report(&file_contents, program.stations());
+            if let Err(e) = std::fs::write(html_file, report) {
+                print_err!(config, "Failed to write file \"{}\": {}", html_file, e);
+                return ExitCode::FAILURE;
+            }
         }
+        None => print!(
+            "{}",
+            coverage.report(&file_contents, program.stations(), color)
+        ),
     }
+    return ExitCode::SUCCESS;
+}
+
+/// Reads and preprocesses a program from a file, logging any errors
+fn load_program(file_name: &str, config: &Config) -> Option<(String, Program)> {
+    let file_contents = read_file(file_name, config)?;
+    match Program::with_config(&file_contents, &Namespace::builtin(), config) {
+        Ok(program) => return Some((file_contents, program)),
+        Err(e) => {
+            print_err!(config, "{}", e.pretty_msg(&file_contents));
+            return None;
+        }
+    }
+}
+
+/// Reads a source file, logging any errors
+fn read_file(file_name: &str, config: &Config) -> Option<String> {
+    debug!(config, 1, "Input file:\t{}", file_name);
+    let mut file = match File::open(file_name) {
+        Ok(f) => f,
+        Err(e) => {
+            print_err!(config, "Failed to open file \"{}\": {}", file_name, e);
+            return None;
+        }
+    };
+    debug!(config, 2, "Opened file");
+    let mut file_contents = String::new();
+    let bytes_read = match file.read_to_string(&mut file_contents) {
+        Ok(b) => b,
+        Err(e) => {
+            print_err!(config, "Failed to read file \"{}\": {}", file_name, e);
+            return None;
+        }
+    };
+    debug!(config, 2, "Read {} bytes", bytes_read);
+    debug!(
+        config,
+        4, "Contents --------------\n{}\n-----------------------", file_contents
+    );
+    return Some(file_contents);
 }
 
 // CLI argument parsing struct
 #[derive(Parser)]
-#[command(version)]
+#[command(version, args_conflicts_with_subcommands = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Running a file without a subcommand is the same as `factory run`
+    #[command(flatten)]
+    run: RunArgs,
+
+    /// Increase debug logging level, can be supplied multiple times
+    #[arg(short = 'd', long = "verbose", action = clap::ArgAction::Count, global = true)]
+    debug_level: u8,
+
+    /// Disable colored terminal output
+    #[arg(long = "no-color", global = true)]
+    no_color: bool,
+
+    /// Make pallets take a step to travel along each cell of a conveyor belt
+    #[arg(long, global = true)]
+    physical_belts: bool,
+
+    /// Run stations that don't do I/O on multiple threads, output is the same
+    /// as running on one
+    #[arg(long, global = true)]
+    parallel: bool,
+
+    /// Run programs exactly as written, without collapsing joints, folding
+    /// constants or removing unreachable stations first
+    #[arg(long = "no-optimize", global = true)]
+    no_optimize: bool,
+
+    /// Seed for random stations, taken from the clock if not given. The seed
+    /// used is printed with `-d`
+    #[arg(long, global = true)]
+    seed: Option<u64>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run a program
+    Run(RunArgs),
+    /// Report the stations and belts of a program that were never used, from
+    /// coverage recorded with `--coverage`
+    Coverage {
+        /// Conveyor program the coverage was recorded from
+        file: String,
+
+        /// Coverage file
+        coverage_file: String,
+
+        /// Write the report as HTML to a file
+        #[arg(long, value_name = "FILE")]
+        html: Option<String>,
+    },
+    /// Step through a program interactively, with breakpoints
+    Debug {
+        /// Conveyor program to debug
+        file: String,
+    },
+    /// Transpile a program to a standalone Rust program, which compiles with
+    /// `rustc`
+    Build {
+        /// Conveyor program to transpile
+        file: String,
+
+        /// Write the Rust source code to a file instead of printing it
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<String>,
+    },
+    /// Continue a program from a snapshot saved with `--snapshot`
+    Resume {
+        /// Snapshot file, overwritten if the program is stopped again
+        snapshot: String,
+
+        /// Stop and save the snapshot again after this many steps in total
+        #[arg(long, value_name = "STEPS")]
+        snapshot_at: Option<usize>,
+    },
+}
+
+#[derive(Args)]
+struct RunArgs {
     /// Conveyor program to execute
     file: Option<String>,
 
@@ -66,11 +545,53 @@ struct Cli {
     #[arg(short, long)]
     benchmark: bool,
 
-    /// Increase debug logging level, can be supplied multiple times
-    #[arg(short = 'd', long = "verbose", action = clap::ArgAction::Count)]
-    debug_level: u8,
+    /// Print the program compiled to IR instead of running it
+    #[arg(long)]
+    dump_ir: bool,
 
-    /// Disable colored terminal output
-    #[arg(long = "no-color")]
-    no_color: bool,
+    /// Write a trace of every execution event to a file, as JSON Lines
+    #[arg(long, value_name = "FILE")]
+    trace: Option<String>,
+
+    /// Record which stations fired and which belts carried pallets, added to
+    /// the coverage already in the file
+    #[arg(long, value_name = "FILE")]
+    coverage: Option<String>,
+
+    /// Print how often each station was triggered and how long it took, on the
+    /// source and as a table
+    #[arg(long)]
+    profile: bool,
+
+    /// Column to sort the profile table by: index, triggers, in, out, drops or
+    /// time
+    #[arg(long, value_name = "KEY", default_value = "time", requires = "profile")]
+    profile_sort: SortKey,
+
+    /// Redraw the program every step, showing pallets travelling along belts
+    #[arg(long)]
+    animate: bool,
+
+    /// Steps per second when animating
+    #[arg(long, default_value_t = 2.0, requires = "animate")]
+    speed: f64,
+
+    /// Save the state of the program to a file if it's interrupted with
+    /// Ctrl-C, to continue later with `factory resume`
+    #[arg(long, value_name = "FILE")]
+    snapshot: Option<String>,
+
+    /// Stop and save the snapshot after this many steps
+    #[arg(long, value_name = "STEPS", requires = "snapshot")]
+    snapshot_at: Option<usize>,
+
+    /// Write every line of input read by the program, and the step it was read
+    /// in, to a file
+    #[arg(long, value_name = "FILE")]
+    record: Option<String>,
+
+    /// Read the program's input from a session written with `--record`
+    /// instead of stdin, failing if it's read differently
+    #[arg(long, value_name = "FILE", conflicts_with = "record")]
+    replay: Option<String>,
 }
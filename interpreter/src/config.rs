@@ -0,0 +1,312 @@
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Options for preprocessing and running programs
+#[derive(Clone)]
+pub struct Config {
+    /// Debug logging level, higher levels log more detail. Zero disables debug
+    /// logging
+    pub debug_level: u8,
+    /// Destination of debug and error messages
+    pub logger: Arc<dyn Logger>,
+    /// Pallets take a step to travel along each cell of a conveyor belt,
+    /// instead of arriving in the next step
+    pub physical_belts: bool,
+    /// Run the procedures of pure stations triggered in the same step on
+    /// multiple threads
+    pub parallel: bool,
+    /// Optimize programs before compiling them to IR
+    pub optimize: bool,
+    /// Seed for the random number generators of random stations
+    pub seed: u64,
+    /// Source of time for time stations
+    pub clock: Arc<dyn Clock>,
+}
+impl Default for Config {
+    /// No debug logging, with uncolored messages printed to stdout, a seed
+    /// taken from the clock, and the system's clock
+    fn default() -> Self {
+        return Self {
+            debug_level: 0,
+            logger: Arc::new(TerminalLogger { color: false }),
+            physical_belts: false,
+            parallel: false,
+            optimize: true,
+            seed: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_nanos() as u64),
+            clock: Arc::new(SystemClock::new()),
+        };
+    }
+}
+impl Config {
+    /// Sets the debug logging level
+    pub fn with_debug_level(self, debug_level: u8) -> Self {
+        return Self {
+            debug_level,
+            ..self
+        };
+    }
+    /// Sets whether pallets take a step to travel along each cell of a belt
+    pub fn with_physical_belts(self, physical_belts: bool) -> Self {
+        return Self {
+            physical_belts,
+            ..self
+        };
+    }
+    /// Sets whether pure stations run on multiple threads
+    pub fn with_parallel(self, parallel: bool) -> Self {
+        return Self { parallel, ..self };
+    }
+    /// Sets whether programs are optimized before compiling them to IR
+    pub fn with_optimize(self, optimize: bool) -> Self {
+        return Self { optimize, ..self };
+    }
+    /// Sets the seed for the random number generators of random stations
+    pub fn with_seed(self, seed: u64) -> Self {
+        return Self { seed, ..self };
+    }
+    /// Sets the destination of debug and error messages
+    pub fn with_logger<L: Logger + 'static>(self, logger: L) -> Self {
+        return Self {
+            logger: Arc::new(logger),
+            ..self
+        };
+    }
+    /// Sets the source of time for time stations
+    pub fn with_clock<C: Clock + 'static>(self, clock: C) -> Self {
+        return Self {
+            clock: Arc::new(clock),
+            ..self
+        };
+    }
+}
+
+/// Sink for the messages logged while preprocessing and running programs
+pub trait Logger: Send + Sync {
+    /// Handles a debug message, along with its debug level
+    fn debug(&self, level: u8, msg: &str);
+    /// Handles an error message
+    fn error(&self, msg: &str);
+}
+
+/// Logger that prints to stdout, optionally with terminal colors
+#[derive(Debug, Clone, Copy)]
+pub struct TerminalLogger {
+    /// Color messages using ANSI escape codes
+    pub color: bool,
+}
+impl Logger for TerminalLogger {
+    fn debug(&self, level: u8, msg: &str) {
+        if level > 1 && self.color {
+            println!("\x1b[90m{msg}\x1b[0m");
+        } else {
+            println!("{msg}");
+        }
+    }
+    fn error(&self, msg: &str) {
+        if self.color {
+            println!("\x1b[31m{msg}\x1b[0m");
+        } else {
+            println!("ERROR! {msg}");
+        }
+    }
+}
+
+/// Source of time for the time stations
+pub trait Clock: Send + Sync {
+    /// Monotonic time passed since the clock was created
+    fn elapsed(&self) -> Duration;
+    /// Wall clock time, as time passed since the Unix epoch
+    fn now(&self) -> Duration;
+    /// Blocks for a duration
+    fn sleep(&self, duration: Duration);
+}
+
+/// Clock using the system's time, starting when it's created
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    start: Instant,
+}
+impl SystemClock {
+    pub fn new() -> Self {
+        return Self {
+            start: Instant::now(),
+        };
+    }
+}
+impl Default for SystemClock {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+impl Clock for SystemClock {
+    fn elapsed(&self) -> Duration {
+        return self.start.elapsed();
+    }
+    fn now(&self) -> Duration {
+        return SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+    }
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// Clock whose time only passes when something sleeps, which returns
+/// immediately. Programs using time stations run instantly and the same every
+/// time
+#[derive(Debug, Default)]
+pub struct VirtualClock {
+    /// Wall clock time the clock starts at, since the Unix epoch
+    epoch: Duration,
+    elapsed: Mutex<Duration>,
+}
+impl VirtualClock {
+    /// Creates a clock starting at a wall clock time, since the Unix epoch
+    pub fn new(epoch: Duration) -> Self {
+        return Self {
+            epoch,
+            elapsed: Mutex::new(Duration::ZERO),
+        };
+    }
+}
+impl Clock for VirtualClock {
+    fn elapsed(&self) -> Duration {
+        return *self.elapsed.lock().unwrap();
+    }
+    fn now(&self) -> Duration {
+        return self.epoch + self.elapsed();
+    }
+    fn sleep(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    /// Logger that stores every message
+    #[derive(Default)]
+    struct CaptureLogger(Arc<Mutex<Vec<(u8, String)>>>);
+    impl Logger for CaptureLogger {
+        fn debug(&self, level: u8, msg: &str) {
+            self.0.lock().unwrap().push((level, msg.to_owned()));
+        }
+        fn error(&self, msg: &str) {
+            self.0.lock().unwrap().push((0, msg.to_owned()));
+        }
+    }
+
+    #[test]
+    fn test_logger() {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let config = Config::default()
+            .with_debug_level(3)
+            .with_logger(CaptureLogger(messages.clone()));
+        let program =
+            Program::with_config("[start]═─[println]", &Namespace::builtin(), &config).unwrap();
+        Interpreter::new()
+            .config(config)
+            .stdout(std::io::sink())
+            .run(&program)
+            .unwrap();
+        let messages = messages.lock().unwrap();
+        assert!(messages.iter().all(|(level, _)| *level <= 3));
+        assert!(messages
+            .iter()
+            .any(|(_, msg)| msg == "Finished preprocessing"));
+        assert!(messages
+            .iter()
+            .any(|(_, msg)| msg == " - Procedure triggered on #1 (println)"));
+    }
+
+    #[test]
+    fn test_debug_level() {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let config = Config::default()
+            .with_debug_level(2)
+            .with_logger(CaptureLogger(messages.clone()));
+        debug!(config, 2, "shown");
+        debug!(config, 3, "hidden {}", 3);
+        print_err!(config, "error");
+        assert_eq!(
+            *messages.lock().unwrap(),
+            vec![(2, String::from("shown")), (0, String::from("error"))]
+        );
+    }
+
+    #[test]
+    fn test_virtual_clock() {
+        let epoch = Duration::from_secs(1_700_000_000);
+        for (src, expected) in [
+            ("[start]═─{1500}═─[sleep]═─[clock]═─[println]", "1500\n"),
+            ("[start]═─{2000}═─[sleep]═─[now]═─[println]", "1700000002\n"),
+        ] {
+            let start = Instant::now();
+            let mut output: Vec<u8> = Vec::new();
+            Interpreter::new()
+                .config(Config::default().with_clock(VirtualClock::new(epoch)))
+                .stdout(&mut output)
+                .run_src(src)
+                .unwrap();
+            assert_eq!(String::from_utf8(output).unwrap(), expected);
+            assert!(start.elapsed() < Duration::from_millis(1000));
+        }
+    }
+
+    #[test]
+    fn test_clock_starts_with_run() {
+        let clock = Arc::new(VirtualClock::default());
+        // time passed before a run, like preprocessing, isn't counted
+        clock.sleep(Duration::from_millis(700));
+        let config = Config {
+            clock: clock.clone(),
+            ..Config::default()
+        };
+        let program = Program::new("[start]═─{1500}═─[sleep]═─[clock]═─[println]").unwrap();
+        // the same interpreter reused across runs, with and without IR
+        let mut output: Vec<u8> = Vec::new();
+        let mut interpreter = Interpreter::new().config(config).stdout(&mut output);
+        for _ in 0..2 {
+            interpreter.run(&program).unwrap();
+            interpreter.run_observed(&program, &mut ()).unwrap();
+        }
+        drop(interpreter);
+        assert_eq!(String::from_utf8(output).unwrap(), "1500\n".repeat(4));
+        assert_eq!(clock.elapsed(), Duration::from_millis(700 + 4 * 1500));
+    }
+
+    #[test]
+    fn test_clock_continues_after_stop() {
+        let src = "[start]═─{1500}═─[sleep]═─[]═─[clock]═─[println]";
+        let program = Program::new(src).unwrap();
+        let config = Config::default().with_clock(VirtualClock::default());
+        let mut output: Vec<u8> = Vec::new();
+        let (_, runtime) = Interpreter::new()
+            .config(config)
+            .stdout(&mut output)
+            .run_until(&program, None, &mut (), &mut |runtime| {
+                runtime.step_count == 2
+            })
+            .unwrap();
+        let snapshot =
+            Snapshot::capture(&runtime.unwrap(), "clock.factory", src, &Config::default());
+
+        // resumed with a new clock, as in another process
+        let runtime = snapshot.restore(&program, src).unwrap();
+        let config = Config::default().with_clock(VirtualClock::default());
+        Interpreter::new()
+            .config(config)
+            .stdout(&mut output)
+            .run_until(&program, Some(runtime), &mut (), &mut |_| false)
+            .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "1500\n");
+    }
+}
@@ -0,0 +1,293 @@
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+/// Observer that records which stations fired and which conveyor belts carried
+/// pallets. Stations are keyed by their location and belts by the location and
+/// bay of the station they lead into, so coverage from many runs of the same
+/// program can be accumulated in one file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Coverage {
+    /// Number of runs recorded
+    pub runs: usize,
+    /// Number of times each station fired
+    pub stations: BTreeMap<String, usize>,
+    /// Number of pallets each belt carried
+    pub belts: BTreeMap<String, usize>,
+}
+impl Coverage {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    /// Loads coverage from a file, or starts empty if it doesn't exist yet
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(contents) => return Ok(serde_json::from_str(&contents)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::new()),
+            Err(e) => return Err(e),
+        }
+    }
+
+    /// Saves coverage to a file, to be accumulated by later runs
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        return fs::write(path, serde_json::to_string_pretty(self)?);
+    }
+
+    /// Number of times a station fired
+    pub fn station_count(&self, station: &Station) -> usize {
+        return *self.stations.get(&station_key(station)).unwrap_or(&0);
+    }
+
+    /// Number of pallets carried by the belt into a station's bay
+    pub fn belt_count(&self, dest: &Station, bay: usize) -> usize {
+        return *self.belts.get(&belt_key(dest, bay)).unwrap_or(&0);
+    }
+
+    /// Renders a plain text report: a summary, the never used stations, and the
+    /// source code with never used stations and belts marked. If colored they
+    /// are highlighted red, otherwise underlined with `^` on the following line
+    pub fn report(&self, src: &str, stations: &[Station], color: bool) -> String {
+        let mut output = self.summary(stations);
+        for (i, station) in stations.iter().enumerate() {
+            if self.station_count(station) == 0 {
+                output += &format!(
+                    "never fired: #{i} [{}] at {}\n",
+                    station.logic.id(),
+                    station.loc
+                );
+            }
+        }
+        output.push('\n');
+
+        let lines: Vec<Vec<char>> = src.split('\n').map(|l| l.chars().collect()).collect();
+        let usage = self.usage_map(&lines, stations);
+        for (line, line_usage) in lines.iter().zip(usage) {
+            let mut markers = String::new();
+            for (c, used) in line.iter().zip(line_usage) {
+                let unused = used == Some(false);
+                if color && unused {
+                    output += &format!("\x1b[1;31m{c}\x1b[0m");
+                } else {
+                    output.push(*c);
+                }
+                markers.push(if unused { '^' } else { ' ' });
+            }
+            output.push('\n');
+            if !color && markers.contains('^') {
+                output += markers.trim_end();
+                output.push('\n');
+            }
+        }
+        return output;
+    }
+
+    /// Renders a standalone HTML report, with the source code colored by whether
+    /// each station and belt was used
+    pub fn html_report(&self, src: &str, stations: &[Station]) -> String {
+        let lines: Vec<Vec<char>> = src.split('\n').map(|l| l.chars().collect()).collect();
+        let usage = self.usage_map(&lines, stations);
+        let mut source = String::new();
+        for (line, line_usage) in lines.iter().zip(usage) {
+            let mut current = None;
+            for (c, used) in line.iter().zip(line_usage) {
+                if used != current {
+                    if current.is_some() {
+                        source += "</span>";
+                    }
+                    match used {
+                        Some(true) => source += "<span class=\"used\">",
+                        Some(false) => source += "<span class=\"unused\">",
+                        None => {}
+                    }
+                    current = used;
+                }
+                match c {
+                    '<' => source += "&lt;",
+                    '>' => source += "&gt;",
+                    '&' => source += "&amp;",
+                    _ => source.push(*c),
+                }
+            }
+            if current.is_some() {
+                source += "</span>";
+            }
+            source.push('\n');
+        }
+        return format!(
+            "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>FactoryScript coverage</title>
+<style>
+body {{ font-family: monospace; }}
+.used {{ background: #c8f0c8; }}
+.unused {{ background: #f5b5b5; }}
+</style>
+</head>
+<body>
+<pre>{}</pre>
+<pre>{source}</pre>
+</body>
+</html>
+",
+            self.summary(stations)
+        );
+    }
+
+    /// Percentages of stations fired and belts used
+    fn summary(&self, stations: &[Station]) -> String {
+        let fired = stations
+            .iter()
+            .filter(|station| self.station_count(station) > 0)
+            .count();
+        let mut belts = 0;
+        let mut used_belts = 0;
+        for station in stations.iter() {
+            for bay in 0..station.in_bays.len() {
+                belts += 1;
+                if self.belt_count(station, bay) > 0 {
+                    used_belts += 1;
+                }
+            }
+        }
+        return format!(
+            "runs: {}\nstations fired: {fired}/{} ({:.1}%)\nbelts used: {used_belts}/{belts} ({:.1}%)\n",
+            self.runs,
+            stations.len(),
+            percentage(fired, stations.len()),
+            percentage(used_belts, belts)
+        );
+    }
+
+    /// Marks every cell of the source that is part of a station or belt with
+    /// whether it was used
+    fn usage_map(&self, lines: &[Vec<char>], stations: &[Station]) -> Vec<Vec<Option<bool>>> {
+        let mut usage: Vec<Vec<Option<bool>>> =
+            lines.iter().map(|line| vec![None; line.len()]).collect();
+        for station in stations.iter() {
+            let used = self.station_count(station) > 0;
+            for col in station.loc.pos.col..(station.loc.pos.col + station.loc.len) {
+                usage[station.loc.pos.line][col] = Some(used);
+            }
+            for out_bay in station.out_bays.iter() {
+                let (dest, bay) = out_bay.dest;
+                let used = self.belt_count(&stations[dest], bay) > 0;
+                for pos in out_bay.path.iter() {
+                    usage[pos.line][pos.col] = Some(used);
+                }
+            }
+        }
+        return usage;
+    }
+}
+impl Observer for Coverage {
+    fn on_event(&mut self, event: &Event, stations: &[Station]) {
+        match *event {
+            Event::StepStart { step: 0 } => self.runs += 1,
+            // stations without inputs, like the start station, are never
+            // triggered, they fire by spawning pallets
+            Event::PalletProduced { station, .. } if stations[station].logic.inputs() == 0 => {
+                *self
+                    .stations
+                    .entry(station_key(&stations[station]))
+                    .or_insert(0) += 1;
+            }
+            Event::StationTriggered { station } => {
+                *self
+                    .stations
+                    .entry(station_key(&stations[station]))
+                    .or_insert(0) += 1;
+            }
+            Event::PalletMoved { to, bay, .. } => {
+                *self.belts.entry(belt_key(&stations[to], bay)).or_insert(0) += 1;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn station_key(station: &Station) -> String {
+    return station.loc.pos.to_string();
+}
+
+fn belt_key(dest: &Station, bay: usize) -> String {
+    return format!("{}#{bay}", dest.loc.pos);
+}
+
+fn percentage(n: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 100.0;
+    }
+    return n as f64 / total as f64 * 100.0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn branch_src(condition: &str) -> String {
+        return format!(
+            "\
+[println]─═{{\"west\"}}─═[branch]═─{{\"east\"}}═─[println]
+                      │   └─═{{{condition}}}─═[start]
+                      └────────────{}╝",
+            "─".repeat(condition.len())
+        );
+    }
+
+    fn run(src: &str, coverage: &mut Coverage) -> Program {
+        let program = Program::new(src).unwrap();
+        Interpreter::new()
+            .stdout(io::sink())
+            .run_observed(&program, coverage)
+            .unwrap();
+        return program;
+    }
+
+    #[test]
+    fn test_coverage_report() {
+        let src = branch_src("true");
+        let mut coverage = Coverage::new();
+        let program = run(&src, &mut coverage);
+        let report = coverage.report(&src, program.stations(), false);
+        assert!(report.starts_with(
+            "runs: 1\nstations fired: 5/7 (71.4%)\nbelts used: 5/7 (71.4%)\n\
+            never fired: #0 [println] at 1:0-9\nnever fired: #1 [assign] at 1:11-19\n"
+        ));
+        assert!(report.contains(
+            "\n\n[println]─═{\"west\"}─═[branch]═─{\"east\"}═─[println]\n\
+            ^^^^^^^^^^^^^^^^^^^^^\n                      │"
+        ));
+    }
+
+    #[test]
+    fn test_coverage_accumulate() {
+        let mut coverage = Coverage::new();
+        run(&branch_src("true"), &mut coverage);
+        let program = run(&branch_src("false"), &mut coverage);
+        let report = coverage.report(&branch_src("false"), program.stations(), false);
+        assert!(report
+            .starts_with("runs: 2\nstations fired: 7/7 (100.0%)\nbelts used: 7/7 (100.0%)\n\n"));
+        assert!(!report.contains('^'));
+
+        let file = std::env::temp_dir().join("factory_coverage_test.json");
+        coverage.save(&file).unwrap();
+        assert_eq!(Coverage::load(&file).unwrap(), coverage);
+        fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn test_coverage_html() {
+        let src = branch_src("true");
+        let mut coverage = Coverage::new();
+        let program = run(&src, &mut coverage);
+        let html = coverage.html_report(&src, program.stations());
+        assert!(html.contains(
+            "<span class=\"unused\">[println]─═{\"west\"}─═</span><span class=\"used\">[branch]═─"
+        ));
+    }
+}
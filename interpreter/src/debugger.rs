@@ -0,0 +1,397 @@
+use std::io;
+
+use crate::*;
+use preprocessor::station_parser::literal_parser::parse_assign_literal;
+
+const HELP: &str = "\
+Stations are referred to by index (#3 or 3), location (line:col), or identifier
+  step [n]                   run n steps (default 1)
+  continue                   run until a breakpoint station is about to fire
+  break <station>            set a breakpoint
+  delete [station]           remove breakpoints, or all of them
+  breakpoints                list breakpoints
+  stations                   list every station and its bays
+  inspect <station>          show a station's bays, state and connections
+  pallets                    list the pallets moving in the next step
+  set <station> <bay> <literal|none>
+                             replace the pallet in a station's bay
+  quit                       stop debugging";
+
+/// Interactive debugger that runs a program one step at a time. Commands are
+/// read from the interpreter's input and responses are written to its output,
+/// alongside the program's own input and output
+pub struct Debugger<'p> {
+    program: &'p Program,
+    /// Indices of the stations to stop at before they fire
+    breakpoints: Vec<usize>,
+}
+impl<'p> Debugger<'p> {
+    pub fn new(program: &'p Program) -> Self {
+        return Self {
+            program,
+            breakpoints: Vec::new(),
+        };
+    }
+
+    /// Runs the debugger until the user quits or the input ends
+    pub fn run(&mut self, interpreter: &mut Interpreter) -> io::Result<()> {
+        let mut ctx = interpreter.context();
+        let mut runtime = Runtime::new(self.program.stations().clone(), &mut ctx, &mut ());
+        writeln!(
+            ctx.stdout,
+            "Debugging {} stations, type \"help\" for a list of commands",
+            runtime.stations.len()
+        )?;
+        loop {
+            write!(ctx.stdout, "(factory) ")?;
+            ctx.stdout.flush()?;
+            let mut line = String::new();
+            if ctx.stdin.read_line(&mut line)? == 0 {
+                writeln!(ctx.stdout)?;
+                return Ok(());
+            }
+            let args: Vec<&str> = line.split_whitespace().collect();
+            if args.is_empty() {
+                continue;
+            }
+            if !self.command(&args, &line, &mut runtime, &mut ctx)? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Executes a command, returns false if the debugger should quit
+    fn command(
+        &mut self,
+        args: &[&str],
+        line: &str,
+        runtime: &mut Runtime,
+        ctx: &mut Context,
+    ) -> io::Result<bool> {
+        match args[0] {
+            "step" | "s" => {
+                let n = match args.get(1).map(|n| n.parse::<usize>()) {
+                    None => 1,
+                    Some(Ok(n)) => n,
+                    Some(Err(_)) => {
+                        writeln!(ctx.stdout, "Invalid number of steps \"{}\"", args[1])?;
+                        return Ok(true);
+                    }
+                };
+                for _ in 0..n {
+                    if runtime.is_finished() || !step(runtime, ctx)? {
+                        break;
+                    }
+                }
+                print_status(runtime, ctx)?;
+            }
+            "continue" | "c" => {
+                if runtime.is_mid_step() && !step(runtime, ctx)? {
+                    return Ok(true);
+                }
+                while !runtime.is_finished() {
+                    runtime.deliver(ctx, &mut ());
+                    let hits: Vec<usize> = runtime
+                        .triggered()
+                        .into_iter()
+                        .filter(|i| self.breakpoints.contains(i))
+                        .collect();
+                    if !hits.is_empty() {
+                        for i in hits {
+                            writeln!(
+                                ctx.stdout,
+                                "Breakpoint on {}",
+                                describe(&runtime.stations, i)
+                            )?;
+                        }
+                        break;
+                    }
+                    if !step(runtime, ctx)? {
+                        return Ok(true);
+                    }
+                }
+                print_status(runtime, ctx)?;
+            }
+            "break" | "b" => {
+                for i in self.targets(args.get(1), runtime, ctx)? {
+                    if !self.breakpoints.contains(&i) {
+                        self.breakpoints.push(i);
+                    }
+                    writeln!(
+                        ctx.stdout,
+                        "Breakpoint set on {}",
+                        describe(&runtime.stations, i)
+                    )?;
+                }
+                self.breakpoints.sort();
+            }
+            "delete" | "d" => {
+                if args.len() == 1 {
+                    self.breakpoints.clear();
+                    writeln!(ctx.stdout, "Removed all breakpoints")?;
+                    return Ok(true);
+                }
+                for i in self.targets(args.get(1), runtime, ctx)? {
+                    if self.breakpoints.contains(&i) {
+                        self.breakpoints.retain(|b| *b != i);
+                        writeln!(
+                            ctx.stdout,
+                            "Breakpoint removed from {}",
+                            describe(&runtime.stations, i)
+                        )?;
+                    }
+                }
+            }
+            "breakpoints" => {
+                if self.breakpoints.is_empty() {
+                    writeln!(ctx.stdout, "No breakpoints")?;
+                }
+                for i in self.breakpoints.iter() {
+                    writeln!(ctx.stdout, "{}", describe(&runtime.stations, *i))?;
+                }
+            }
+            "stations" | "ls" => {
+                for i in 0..runtime.stations.len() {
+                    writeln!(
+                        ctx.stdout,
+                        "{}  bays: [{}]",
+                        describe(&runtime.stations, i),
+                        runtime.stations[i]
+                            .in_bays
+                            .iter()
+                            .map(describe_bay)
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    )?;
+                }
+            }
+            "inspect" | "i" => {
+                for i in self.targets(args.get(1), runtime, ctx)? {
+                    let station = &runtime.stations[i];
+                    writeln!(ctx.stdout, "{}", describe(&runtime.stations, i))?;
+                    for (bay, pallet) in station.in_bays.iter().enumerate() {
+                        writeln!(ctx.stdout, "  in bay {bay}: {}", describe_bay(pallet))?;
+                    }
+                    for out_bay in station.out_bays.iter() {
+                        writeln!(
+                            ctx.stdout,
+                            "  out ({}) to #{}:{}",
+                            out_bay.side, out_bay.dest.0, out_bay.dest.1
+                        )?;
+                    }
+                    if let Some(config) = &station.config {
+                        writeln!(ctx.stdout, "  config: {config}")?;
+                    }
+                    if let Some(state) = &station.state {
+                        writeln!(ctx.stdout, "  state: {state}")?;
+                    }
+                }
+            }
+            "pallets" | "p" => {
+                if runtime.moving_pallets.is_empty() {
+                    writeln!(ctx.stdout, "No moving pallets")?;
+                }
+                for moving in runtime.moving_pallets.iter() {
+                    write!(
+                        ctx.stdout,
+                        "{} from #{} to #{}:{}",
+                        moving.pallet, moving.origin, moving.dest.0, moving.dest.1
+                    )?;
+                    if moving.delay > 0 {
+                        write!(ctx.stdout, " (arrives in {} steps)", moving.delay + 1)?;
+                    }
+                    writeln!(ctx.stdout)?;
+                }
+            }
+            "set" => {
+                let targets = self.targets(args.get(1), runtime, ctx)?;
+                if targets.len() > 1 {
+                    writeln!(ctx.stdout, "\"{}\" refers to multiple stations", args[1])?;
+                    return Ok(true);
+                }
+                let Some(i) = targets.first().copied() else {
+                    return Ok(true);
+                };
+                let Some(bay) = args.get(2).and_then(|bay| bay.parse::<usize>().ok()) else {
+                    writeln!(ctx.stdout, "Expected a bay number")?;
+                    return Ok(true);
+                };
+                if bay >= runtime.stations[i].in_bays.len() {
+                    writeln!(
+                        ctx.stdout,
+                        "{} has no bay {bay}",
+                        describe(&runtime.stations, i)
+                    )?;
+                    return Ok(true);
+                }
+                // the literal is the rest of the line, it may contain spaces
+                let literal = line
+                    .trim()
+                    .splitn(4, char::is_whitespace)
+                    .nth(3)
+                    .unwrap_or("")
+                    .trim();
+                let pallet = if literal == "none" {
+                    None
+                } else {
+                    match parse_assign_literal(&literal.to_owned(), SourceSpan::zero()) {
+                        Ok(p) => Some(p),
+                        Err(e) => {
+                            writeln!(ctx.stdout, "{}", e.msg)?;
+                            return Ok(true);
+                        }
+                    }
+                };
+                writeln!(
+                    ctx.stdout,
+                    "In bay {bay} of {} set to {}",
+                    describe(&runtime.stations, i),
+                    describe_bay(&pallet)
+                )?;
+                runtime.stations[i].in_bays[bay] = pallet;
+            }
+            "help" | "h" => writeln!(ctx.stdout, "{HELP}")?,
+            "quit" | "q" => return Ok(false),
+            _ => writeln!(
+                ctx.stdout,
+                "Unknown command \"{}\", type \"help\" for a list of commands",
+                args[0]
+            )?,
+        }
+        return Ok(true);
+    }
+
+    /// Finds the stations a command argument refers to, by index (#3 or 3),
+    /// location (line:col), or identifier
+    fn targets(
+        &self,
+        arg: Option<&&str>,
+        runtime: &Runtime,
+        ctx: &mut Context,
+    ) -> io::Result<Vec<usize>> {
+        let Some(arg) = arg else {
+            writeln!(ctx.stdout, "Expected a station")?;
+            return Ok(Vec::new());
+        };
+        let stations = &runtime.stations;
+        let targets: Vec<usize> = if let Ok(i) = arg.trim_start_matches('#').parse::<usize>() {
+            (0..stations.len()).filter(|j| *j == i).collect()
+        } else if let Some((line, col)) = arg.split_once(':') {
+            match (line.parse::<usize>(), col.parse::<usize>()) {
+                (Ok(line), Ok(col)) if line > 0 => {
+                    let pos = SourcePos::new(line - 1, col);
+                    preprocessor::conveyor_belt_parser::get_station_at(stations, pos)
+                        .into_iter()
+                        .collect()
+                }
+                _ => Vec::new(),
+            }
+        } else {
+            (0..stations.len())
+                .filter(|i| stations[*i].logic.has_id(arg))
+                .collect()
+        };
+        if targets.is_empty() {
+            writeln!(ctx.stdout, "No station matches \"{arg}\"")?;
+        }
+        return Ok(targets);
+    }
+}
+
+/// Runs the rest of the current step, printing any runtime error. Returns false
+/// if there was an error
+fn step(runtime: &mut Runtime, ctx: &mut Context) -> io::Result<bool> {
+    if let Err(e) = runtime.step(ctx, &mut ()) {
+        writeln!(ctx.stdout, "{} @ {}: {}", e.t, e.loc, e.msg)?;
+        return Ok(false);
+    }
+    return Ok(true);
+}
+
+/// Prints where the program is stopped
+fn print_status(runtime: &Runtime, ctx: &mut Context) -> io::Result<()> {
+    if runtime.is_finished() {
+        writeln!(
+            ctx.stdout,
+            "Program finished after {} steps",
+            runtime.step_count
+        )?;
+    } else if runtime.is_mid_step() {
+        writeln!(
+            ctx.stdout,
+            "Stopped in step {}, before stations fire",
+            runtime.step_count
+        )?;
+    } else {
+        writeln!(
+            ctx.stdout,
+            "Stopped before step {}, {} moving pallets",
+            runtime.step_count,
+            runtime.moving_pallets.len()
+        )?;
+    }
+    return Ok(());
+}
+
+fn describe(stations: &[Station], i: usize) -> String {
+    return format!("#{i} [{}] at {}", stations[i].logic.id(), stations[i].loc);
+}
+
+fn describe_bay(pallet: &Option<Pallet>) -> String {
+    return match pallet {
+        Some(p) => p.to_string(),
+        None => String::from("none"),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs the debugger with a list of commands, returning the output
+    fn debug(src: &str, commands: &str) -> String {
+        let program = Program::new(src).unwrap();
+        let mut output = Vec::new();
+        Debugger::new(&program)
+            .run(
+                &mut Interpreter::new()
+                    .stdin(commands.as_bytes())
+                    .stdout(&mut output),
+            )
+            .unwrap();
+        return String::from_utf8(output).unwrap();
+    }
+
+    #[test]
+    fn test_step() {
+        let output = debug(
+            "[start]═─{\"hi\"}═─[println]",
+            "pallets\nstep\npallets\nstep 5\nstep\n",
+        );
+        assert!(output.contains("Pallet< > from #0 to #1:0"));
+        assert!(output.contains("Stopped before step 1, 1 moving pallets"));
+        assert!(output.contains("Pallet<s:\"hi\"> from #1 to #2:0"));
+        assert!(output.contains("hi\nProgram finished after 2 steps"));
+    }
+
+    #[test]
+    fn test_breakpoint_and_set() {
+        let output = debug(
+            "[start]═─{\"hi\"}═─[println]",
+            "break println\ncontinue\ninspect 1:18\nset #2 0 \"bye bye\"\ncontinue\n",
+        );
+        assert!(output.contains("Breakpoint set on #2 [println] at 1:17-26"));
+        assert!(output.contains("Breakpoint on #2 [println]"));
+        assert!(output.contains("Stopped in step 1, before stations fire"));
+        assert!(output.contains("  in bay 0: Pallet<s:\"hi\">"));
+        assert!(output.contains("bye bye\nProgram finished after 2 steps"));
+    }
+
+    #[test]
+    fn test_list_stations() {
+        let output = debug("[start]═─[+]", "ls\nbreak #5\nc\nstep\n");
+        assert!(output.contains("#1 [add] at 1:9-12  bays: [none]"));
+        assert!(output.contains("No station matches \"#5\""));
+        assert!(output.contains("Program finished after 1 steps"));
+    }
+}
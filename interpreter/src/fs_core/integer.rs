@@ -0,0 +1,119 @@
+//! Helpers for integer pallet arithmetic, handling overflow and (with the
+//! `bigint` feature) arbitrary precision integers
+
+use std::cmp::Ordering;
+
+#[cfg(feature = "bigint")]
+use num_traits::{ToPrimitive, Zero};
+
+use crate::*;
+
+/// Integer arithmetic operations
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+}
+impl IntOp {
+    /// Performs the operation, returning `None` if it overflows
+    fn checked(self, num1: i64, num2: i64) -> Option<i64> {
+        match self {
+            IntOp::Add => num1.checked_add(num2),
+            IntOp::Subtract => num1.checked_sub(num2),
+            IntOp::Multiply => num1.checked_mul(num2),
+            IntOp::Divide => num1.checked_div(num2),
+            IntOp::Modulo => num1.checked_rem(num2),
+        }
+    }
+
+    #[cfg(feature = "bigint")]
+    fn big(self, num1: &BigInt, num2: &BigInt) -> BigInt {
+        match self {
+            IntOp::Add => num1 + num2,
+            IntOp::Subtract => num1 - num2,
+            IntOp::Multiply => num1 * num2,
+            IntOp::Divide => num1 / num2,
+            IntOp::Modulo => num1 % num2,
+        }
+    }
+}
+
+/// Checks if a pallet holds an integer of any width
+pub fn is_integer(pallet: &Pallet) -> bool {
+    match pallet {
+        Pallet::Int(_) => true,
+        #[cfg(feature = "bigint")]
+        Pallet::BigInt(_) => true,
+        _ => false,
+    }
+}
+
+/// Checks if an integer pallet is zero
+fn is_zero(pallet: &Pallet) -> bool {
+    match pallet {
+        Pallet::Int(num) => *num == 0,
+        #[cfg(feature = "bigint")]
+        Pallet::BigInt(num) => num.is_zero(),
+        _ => false,
+    }
+}
+
+/// Applies an arithmetic operation to two integer pallets. If the result
+/// overflows it is promoted to a big integer when the `bigint` feature is
+/// enabled, otherwise an error is returned
+pub fn integer_arithmetic(op: IntOp, pallet1: &Pallet, pallet2: &Pallet) -> Result<Pallet, String> {
+    if (op == IntOp::Divide || op == IntOp::Modulo) && is_zero(pallet2) {
+        return Err(String::from("Attempted divide by zero"));
+    }
+    if let (Pallet::Int(num1), Pallet::Int(num2)) = (pallet1, pallet2) {
+        if let Some(num) = op.checked(*num1, *num2) {
+            return Ok(Pallet::Int(num));
+        }
+    }
+    #[cfg(feature = "bigint")]
+    if let (Some(num1), Some(num2)) = (to_bigint(pallet1), to_bigint(pallet2)) {
+        return Ok(from_bigint(op.big(&num1, &num2)));
+    }
+    if is_integer(pallet1) && is_integer(pallet2) {
+        return Err(format!(
+            "Integer overflow, {pallet1} and {pallet2} can't be combined"
+        ));
+    }
+    return Err(format!(
+        "Expected integer pallets, received {pallet1} and {pallet2}"
+    ));
+}
+
+/// Compares two integer pallets of any width
+pub fn compare_integers(pallet1: &Pallet, pallet2: &Pallet) -> Option<Ordering> {
+    match (pallet1, pallet2) {
+        (Pallet::Int(num1), Pallet::Int(num2)) => Some(num1.cmp(num2)),
+        #[cfg(feature = "bigint")]
+        _ => Some(to_bigint(pallet1)?.cmp(&to_bigint(pallet2)?)),
+        #[cfg(not(feature = "bigint"))]
+        _ => None,
+    }
+}
+
+/// Converts an integer pallet of any width into a big integer
+#[cfg(feature = "bigint")]
+pub fn to_bigint(pallet: &Pallet) -> Option<BigInt> {
+    match pallet {
+        Pallet::Int(num) => Some(BigInt::from(*num)),
+        Pallet::BigInt(num) => Some(num.clone()),
+        _ => None,
+    }
+}
+
+/// Creates an integer pallet from a big integer, using the fixed width `Int`
+/// variant whenever the value fits so that equal values have equal pallets
+#[cfg(feature = "bigint")]
+pub fn from_bigint(num: BigInt) -> Pallet {
+    match num.to_i64() {
+        Some(num) => Pallet::Int(num),
+        None => Pallet::BigInt(num),
+    }
+}
@@ -0,0 +1,79 @@
+//! Helpers for map pallets, which keep their entries sorted by key so that maps
+//! with the same entries are equal however they were built
+
+use std::cmp::Ordering;
+
+use crate::*;
+use fs_core::integer::*;
+
+/// Entries of a map pallet, sorted by key
+pub type Entries = Vec<(Pallet, Pallet)>;
+
+/// Checks if a pallet can be used as a map key
+pub fn is_key(pallet: &Pallet) -> bool {
+    return matches!(
+        pallet,
+        Pallet::Bool(_) | Pallet::Char(_) | Pallet::String(_)
+    ) || is_integer(pallet);
+}
+
+/// Orders keys by type (booleans, integers, characters, then strings) and then
+/// by value
+fn compare_keys(key1: &Pallet, key2: &Pallet) -> Ordering {
+    fn rank(key: &Pallet) -> u8 {
+        return match key {
+            Pallet::Bool(_) => 0,
+            Pallet::Char(_) => 2,
+            Pallet::String(_) => 3,
+            _ => 1,
+        };
+    }
+    return match (key1, key2) {
+        (Pallet::Bool(b1), Pallet::Bool(b2)) => b1.cmp(b2),
+        (Pallet::Char(c1), Pallet::Char(c2)) => c1.cmp(c2),
+        (Pallet::String(s1), Pallet::String(s2)) => s1.cmp(s2),
+        (k1, k2) if is_integer(k1) && is_integer(k2) => {
+            compare_integers(k1, k2).unwrap_or(Ordering::Equal)
+        }
+        (k1, k2) => rank(k1).cmp(&rank(k2)),
+    };
+}
+
+/// Error message for a pallet that can't be used as a key
+pub fn key_error(key: &Pallet) -> String {
+    return format!("Map keys must be strings, integers, characters or booleans, received {key}");
+}
+
+/// Looks up the value of a key
+pub fn get<'a>(entries: &'a Entries, key: &Pallet) -> Option<&'a Pallet> {
+    let i = entries
+        .binary_search_by(|(k, _)| compare_keys(k, key))
+        .ok()?;
+    return Some(&entries[i].1);
+}
+
+/// Sets the value of a key, replacing any value it already had. Returns whether
+/// the key was already in the map
+pub fn insert(entries: &mut Entries, key: Pallet, value: Pallet) -> Result<bool, String> {
+    if !is_key(&key) {
+        return Err(key_error(&key));
+    }
+    match entries.binary_search_by(|(k, _)| compare_keys(k, &key)) {
+        Ok(i) => {
+            entries[i].1 = value;
+            return Ok(true);
+        }
+        Err(i) => {
+            entries.insert(i, (key, value));
+            return Ok(false);
+        }
+    }
+}
+
+/// Removes a key and its value, returning the value if the key was in the map
+pub fn remove(entries: &mut Entries, key: &Pallet) -> Option<Pallet> {
+    let i = entries
+        .binary_search_by(|(k, _)| compare_keys(k, key))
+        .ok()?;
+    return Some(entries.remove(i).1);
+}
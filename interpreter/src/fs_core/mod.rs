@@ -1,6 +1,18 @@
 pub mod constants;
+pub mod integer;
+pub mod map;
 pub mod stations;
 
+use std::io::{BufRead, Write};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Config, Direction, Station};
+
+#[cfg(feature = "bigint")]
+pub use num_bigint::BigInt;
+
 pub const BELT_CHARS: &str = "─│┌┐└┘═║╔╗╚╝";
 pub const SINGLE_BELT_CHARS: &str = "─│┌┐└┘";
 pub const DOUBLE_BELT_CHARS: &str = "═║╔╗╚╝";
@@ -10,8 +22,106 @@ pub const SOUTH_BELT_CHARS: &str = "│┌┐║╔╗";
 pub const WEST_BELT_CHARS: &str = "─┐┘═╗╝";
 
 /// Defines a station and all the required information and functionality
+pub trait StationType: std::fmt::Debug + Send + Sync {
+    /// Identifier
+    fn id(&self) -> &str;
+    /// Alternate identifier
+    fn alt_id(&self) -> Option<&str> {
+        return None;
+    }
+    /// Minimum number of inputs required for this station to trigger its procedure
+    fn inputs(&self) -> usize;
+    /// Function to check whether a station has a certain ID
+    fn has_id(&self, query: &str) -> bool {
+        return self.id() == query || (self.alt_id().is_some_and(|alt_id| alt_id == query));
+    }
+    /// Checks whether the station's procedure should run, given the contents of
+    /// its input bays. By default this is when at least `inputs` bays are occupied
+    fn triggered(&self, in_bays: &[Option<Pallet>]) -> bool {
+        let occupied_bays = in_bays.iter().filter(|bay| bay.is_some()).count();
+        return self.inputs() > 0 && occupied_bays >= self.inputs();
+    }
+    /// Pallet spawned by this station when the program begins, if any
+    fn spawn(&self) -> Option<Pallet> {
+        return None;
+    }
+    /// Whether this is the program's entry point, every program has exactly one
+    fn is_entry(&self) -> bool {
+        return false;
+    }
+    /// Whether the procedure only touches its own station, and never the
+    /// context's streams, so it can run on another thread alongside other
+    /// stations. Stations that aren't pure always run in order on the main thread
+    fn pure(&self) -> bool {
+        return false;
+    }
+    /// Instruction the station is compiled to. Stations that aren't simple
+    /// enough to have their own opcode call their procedure
+    fn opcode(&self) -> Opcode {
+        return Opcode::Call;
+    }
+    /// Station's procedure, run when the station is triggered. Takes the station
+    /// instance (for its input bays, configuration and state) and returns what to
+    /// do next, or an error message in a String if it fails
+    fn procedure(&self, station: &mut Station, ctx: &mut Context) -> Result<Output, String>;
+}
+
+/// Environment of the running program that station procedures have access to
+pub struct Context<'a> {
+    /// Interpreter configuration
+    pub config: &'a Config,
+    /// Input stream read by the program
+    pub stdin: &'a mut dyn BufRead,
+    /// Output stream written to by the program
+    pub stdout: &'a mut dyn Write,
+    /// Error stream written to by the program
+    pub stderr: &'a mut dyn Write,
+    /// Elapsed time of the configured clock when the program started running,
+    /// or continued after being stopped
+    pub start: Duration,
+    /// Time the program had already been running for when it continued, zero
+    /// if it didn't stop
+    pub offset: Duration,
+}
+impl Context<'_> {
+    /// Time the program has been running for by the configured clock, which
+    /// time stations measure
+    pub fn elapsed(&self) -> Duration {
+        return self.offset + self.config.clock.elapsed().saturating_sub(self.start);
+    }
+}
+
+/// Result of a station's procedure
+#[derive(Debug, Clone, PartialEq)]
+pub enum Output {
+    /// Send a pallet out of every output bay
+    Emit(Pallet),
+    /// Send pallets out of the output bays on specific sides of the station
+    EmitTo(Vec<(Direction, Pallet)>),
+    /// Produce nothing
+    Drop,
+    /// Stop program execution
+    Halt,
+}
+
+/// Instruction a station is compiled to, for stations the compiled program
+/// runs without calling their procedure
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Opcode {
+    /// Pass through the pallet in the first occupied bay
+    Joint,
+    /// Emit the station's configured literal
+    Assign,
+    /// Stop program execution
+    Exit,
+    /// Call the station's procedure
+    Call,
+}
+
+/// Station type defined by a plain function of its input pallets, used for
+/// most of the builtin stations
 #[derive(Debug)]
-pub struct StationType {
+pub struct BasicStation {
     /// Identifier
     pub id: &'static str,
     /// Alternate identifier
@@ -24,22 +134,104 @@ pub struct StationType {
     /// pallet if successful, and an error message in a String if not
     pub procedure: fn(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String>,
 }
-impl StationType {
-    /// Function to check whether a station has a certain ID
-    pub fn has_id(&self, query: &str) -> bool {
-        return self.id == query || (self.alt_id.is_some_and(|alt_id| alt_id == query));
+impl StationType for BasicStation {
+    fn id(&self) -> &str {
+        return self.id;
+    }
+    fn alt_id(&self) -> Option<&str> {
+        return self.alt_id;
+    }
+    fn inputs(&self) -> usize {
+        return self.inputs;
+    }
+    fn pure(&self) -> bool {
+        return true;
+    }
+    fn procedure(&self, station: &mut Station, _: &mut Context) -> Result<Output, String> {
+        match ((self.procedure)(&station.in_bays)?, self.output) {
+            (Some(p), true) => Ok(Output::Emit(p)),
+            (None, false) => Ok(Output::Drop),
+            (Some(_), false) => Err(String::from(
+                "Station procedure returned pallet unexpectedly",
+            )),
+            (None, true) => Err(String::from(
+                "Station procedure did not return pallet as expected",
+            )),
+        }
     }
 }
 
 /// Instance of a pallet
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Pallet {
     Empty,
     Bool(bool),
     Char(char),
     String(String),
     Int(i64),
+    /// Arbitrary precision integer, only used for values that don't fit in an
+    /// `Int`
+    #[cfg(feature = "bigint")]
+    BigInt(BigInt),
     Float(f64),
+    List(Vec<Pallet>),
+    /// Entries sorted by key, see the `map` module
+    Map(Vec<(Pallet, Pallet)>),
+}
+impl Pallet {
+    /// The pallet's value as print stations write it
+    pub fn text(&self) -> String {
+        return match self {
+            Pallet::Empty => String::new(),
+            Pallet::Char(c) => c.to_string(),
+            Pallet::String(s) => s.clone(),
+            Pallet::Float(f) => f.to_string(),
+            _ => self.literal(),
+        };
+    }
+
+    /// Name of the pallet's type
+    pub fn type_name(&self) -> &'static str {
+        return match self {
+            Pallet::Empty => "empty",
+            Pallet::Bool(_) => "bool",
+            Pallet::Char(_) => "char",
+            Pallet::String(_) => "string",
+            Pallet::Int(_) => "int",
+            #[cfg(feature = "bigint")]
+            Pallet::BigInt(_) => "int",
+            Pallet::Float(_) => "float",
+            Pallet::List(_) => "list",
+            Pallet::Map(_) => "map",
+        };
+    }
+
+    /// The pallet's value written as an assign station literal, which is how
+    /// the values inside lists and maps are printed
+    pub fn literal(&self) -> String {
+        return match self {
+            Pallet::Empty => String::new(),
+            Pallet::Bool(b) => b.to_string(),
+            Pallet::Char(c) => format!("'{c}'"),
+            Pallet::String(s) => format!("\"{s}\""),
+            Pallet::Int(i) => i.to_string(),
+            #[cfg(feature = "bigint")]
+            Pallet::BigInt(i) => i.to_string(),
+            Pallet::Float(f) => format!("{f:?}"),
+            Pallet::List(items) => {
+                let items: Vec<String> = items.iter().map(|p| p.literal()).collect();
+                format!("[{}]", items.join(", "))
+            }
+            Pallet::Map(entries) if entries.is_empty() => String::from("[:]"),
+            Pallet::Map(entries) => {
+                let entries: Vec<String> = entries
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", k.literal(), v.literal()))
+                    .collect();
+                format!("[{}]", entries.join(", "))
+            }
+        };
+    }
 }
 impl std::fmt::Display for Pallet {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -52,7 +244,11 @@ impl std::fmt::Display for Pallet {
                 Pallet::Char(c) => format!("Pallet<c:\'{}\'>", c),
                 Pallet::String(s) => format!("Pallet<s:\"{}\">", s),
                 Pallet::Int(i) => format!("Pallet<i:{}>", i),
+                #[cfg(feature = "bigint")]
+                Pallet::BigInt(i) => format!("Pallet<i:{}>", i),
                 Pallet::Float(f) => format!("Pallet<f:{}>", f),
+                Pallet::List(_) => format!("Pallet<l:{}>", self.literal()),
+                Pallet::Map(_) => format!("Pallet<m:{}>", self.literal()),
             },
         )
     }
@@ -2,42 +2,87 @@ use crate::*;
 use lazy_static::lazy_static;
 
 mod control;
+mod convert;
+mod dict;
 mod io;
+mod list;
 mod math;
+mod memory;
+mod random;
+mod string;
+mod time;
 
 lazy_static! {
-    pub static ref STATION_TYPES: Vec<&'static StationType> = vec![
-        &control::START,
-        &control::EXIT,
-        &control::JOINT,
-        &control::ASSIGN,
-        &control::GATE,
-        &control::FILTER,
-        &io::PRINT,
-        &io::PRINTLN,
-        &io::READLN,
-        &math::ADD,
-        &math::SUBTRACT,
-        &math::MULTIPLY,
-        &math::DIVIDE,
-        &math::MODULO,
-        &math::EQUALS,
-        &math::NOT_EQUALS,
-        &math::GREATER_THAN,
-        &math::LESS_THAN,
-        &math::GREATER_THAN_EQUAL,
-        &math::LESS_THAN_EQUAL,
-        &math::INCREMENT,
-        &math::DECREMENT,
-        &math::AND,
-        &math::NOT,
-        &math::OR,
-    ];
-}
-
-/// Common procedure that returns nothign
-pub fn none_procedure(_: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
-    return Ok(None);
+    /// Namespace of all the builtin station types
+    pub static ref STATION_TYPES: Namespace = {
+        let mut ns = Namespace::new();
+        ns.register(control::START);
+        ns.register(control::EXIT);
+        ns.register(control::JOINT);
+        ns.register(control::ASSIGN);
+        ns.register(control::GATE);
+        ns.register(control::FILTER);
+        ns.register(control::BRANCH);
+        ns.register(io::PRINT);
+        ns.register(io::PRINTLN);
+        ns.register(io::READLN);
+        ns.register(math::ADD);
+        ns.register(math::SUBTRACT);
+        ns.register(math::MULTIPLY);
+        ns.register(math::DIVIDE);
+        ns.register(math::MODULO);
+        ns.register(math::EQUALS);
+        ns.register(math::NOT_EQUALS);
+        ns.register(math::GREATER_THAN);
+        ns.register(math::LESS_THAN);
+        ns.register(math::GREATER_THAN_EQUAL);
+        ns.register(math::LESS_THAN_EQUAL);
+        ns.register(math::INCREMENT);
+        ns.register(math::DECREMENT);
+        ns.register(math::AND);
+        ns.register(math::NOT);
+        ns.register(math::OR);
+        ns.register(convert::INT);
+        ns.register(convert::FLOAT);
+        ns.register(convert::STR);
+        ns.register(convert::CHAR);
+        ns.register(convert::BOOL);
+        ns.register(convert::TYPE);
+        ns.register(memory::COUNT);
+        ns.register(memory::ACCUMULATE);
+        ns.register(memory::LATCH);
+        ns.register(list::PUSH);
+        ns.register(list::POP);
+        ns.register(list::GET);
+        ns.register(list::SET);
+        ns.register(list::LENGTH);
+        ns.register(list::CONCAT);
+        ns.register(list::SLICE);
+        ns.register(list::SORT);
+        ns.register(string::CHAR_AT);
+        ns.register(string::SUBSTRING);
+        ns.register(string::SPLIT);
+        ns.register(string::JOIN);
+        ns.register(string::UPPER);
+        ns.register(string::LOWER);
+        ns.register(string::TRIM);
+        ns.register(string::CONTAINS);
+        ns.register(string::STARTS_WITH);
+        ns.register(string::REPLACE);
+        ns.register(string::FIND);
+        ns.register(dict::INSERT);
+        ns.register(dict::LOOKUP);
+        ns.register(dict::REMOVE);
+        ns.register(dict::KEYS);
+        ns.register(dict::SIZE);
+        ns.register(random::RAND);
+        ns.register(random::RANDINT);
+        ns.register(random::SHUFFLE);
+        ns.register(time::SLEEP);
+        ns.register(time::CLOCK);
+        ns.register(time::NOW);
+        ns
+    };
 }
 
 /// helper function to generate a string listing pallets, used for error messages
@@ -58,3 +103,33 @@ fn list_pallets(pallets: &Vec<Option<Pallet>>) -> String {
     output.push(')');
     return output;
 }
+
+/// Splits a station's pallets into one of a type and the rest of the pallets in
+/// bay order, so that pallet can arrive from any side. When more than one
+/// pallet is of the type, the one in the first bay is split off
+fn split_pallets(
+    pallets: &Vec<Option<Pallet>>,
+    is_type: fn(&Pallet) -> bool,
+) -> Option<(&Pallet, Vec<&Pallet>)> {
+    let matching = pallets
+        .iter()
+        .filter(|p| p.as_ref().is_some_and(is_type))
+        .count();
+    let i = if matching > 1 {
+        0
+    } else {
+        pallets
+            .iter()
+            .position(|p| p.as_ref().is_some_and(is_type))?
+    };
+    if !pallets[i].as_ref().is_some_and(is_type) {
+        return None;
+    }
+    let rest = pallets
+        .iter()
+        .enumerate()
+        .filter(|(j, _)| *j != i)
+        .filter_map(|(_, p)| p.as_ref())
+        .collect();
+    return Some((pallets[i].as_ref()?, rest));
+}
@@ -1,76 +1,199 @@
 use super::*;
 
-pub static START: StationType = StationType {
-    id: "start",
-    alt_id: None,
-    inputs: 0,
-    output: true,
-    procedure: start_procedure,
-};
-fn start_procedure(_: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
-    return Ok(Some(Pallet::Empty));
+/// Entry point of the program, spawns an empty pallet when execution begins
+#[derive(Debug)]
+pub struct Start;
+impl StationType for Start {
+    fn id(&self) -> &str {
+        return "start";
+    }
+    fn inputs(&self) -> usize {
+        return 0;
+    }
+    fn spawn(&self) -> Option<Pallet> {
+        return Some(Pallet::Empty);
+    }
+    fn is_entry(&self) -> bool {
+        return true;
+    }
+    fn pure(&self) -> bool {
+        return true;
+    }
+    fn procedure(&self, _: &mut Station, _: &mut Context) -> Result<Output, String> {
+        return Ok(Output::Drop);
+    }
 }
+pub const START: Start = Start;
 
-pub static EXIT: StationType = StationType {
-    id: "exit",
-    alt_id: None,
-    inputs: 1,
-    output: false,
-    procedure: none_procedure,
-};
+/// Halts the program when it receives any pallet
+#[derive(Debug)]
+pub struct Exit;
+impl StationType for Exit {
+    fn id(&self) -> &str {
+        return "exit";
+    }
+    fn inputs(&self) -> usize {
+        return 1;
+    }
+    fn pure(&self) -> bool {
+        return true;
+    }
+    fn opcode(&self) -> Opcode {
+        return Opcode::Exit;
+    }
+    fn procedure(&self, _: &mut Station, _: &mut Context) -> Result<Output, String> {
+        return Ok(Output::Halt);
+    }
+}
+pub const EXIT: Exit = Exit;
 
-pub static JOINT: StationType = StationType {
-    id: "joint",
-    alt_id: Some(""),
-    inputs: 1,
-    output: true,
-    procedure: none_procedure,
-};
+/// Passes through the first pallet it receives
+#[derive(Debug)]
+pub struct Joint;
+impl StationType for Joint {
+    fn id(&self) -> &str {
+        return "joint";
+    }
+    fn alt_id(&self) -> Option<&str> {
+        return Some("");
+    }
+    fn inputs(&self) -> usize {
+        return 1;
+    }
+    fn pure(&self) -> bool {
+        return true;
+    }
+    fn opcode(&self) -> Opcode {
+        return Opcode::Joint;
+    }
+    fn procedure(&self, station: &mut Station, _: &mut Context) -> Result<Output, String> {
+        match station.in_bays.iter().flatten().next() {
+            Some(p) => Ok(Output::Emit(p.clone())),
+            None => Err(String::from("Expected pallet, received none")),
+        }
+    }
+}
+pub const JOINT: Joint = Joint;
 
-pub static ASSIGN: StationType = StationType {
-    id: "assign",
-    alt_id: None,
-    inputs: 1,
-    output: true,
-    procedure: none_procedure,
-};
+/// Replaces any pallet it receives with the literal in its configuration
+#[derive(Debug)]
+pub struct Assign;
+impl StationType for Assign {
+    fn id(&self) -> &str {
+        return "assign";
+    }
+    fn inputs(&self) -> usize {
+        return 1;
+    }
+    fn pure(&self) -> bool {
+        return true;
+    }
+    fn opcode(&self) -> Opcode {
+        return Opcode::Assign;
+    }
+    fn procedure(&self, station: &mut Station, _: &mut Context) -> Result<Output, String> {
+        match &station.config {
+            Some(p) => Ok(Output::Emit(p.clone())),
+            None => Err(String::from("Assign station is missing its literal")),
+        }
+    }
+}
+pub const ASSIGN: Assign = Assign;
+
+/// Passes a pallet through if it is accompanied by a true boolean pallet
+#[derive(Debug)]
+pub struct Gate;
+impl StationType for Gate {
+    fn id(&self) -> &str {
+        return "gate";
+    }
+    fn inputs(&self) -> usize {
+        return 2;
+    }
+    fn pure(&self) -> bool {
+        return true;
+    }
+    fn procedure(&self, station: &mut Station, _: &mut Context) -> Result<Output, String> {
+        let pallets = &station.in_bays;
+        match (&pallets[0], &pallets[1]) {
+            (Some(Pallet::Bool(b)), Some(pallet)) | (Some(pallet), Some(Pallet::Bool(b))) => {
+                Ok(if *b {
+                    Output::Emit(pallet.clone())
+                } else {
+                    Output::Drop
+                })
+            }
+            _ => {
+                return Err(format!(
+                    "Expected at least one boolean pallet, received {}\n",
+                    list_pallets(pallets)
+                ));
+            }
+        }
+    }
+}
+pub const GATE: Gate = Gate;
 
-pub static GATE: StationType = StationType {
-    id: "gate",
-    alt_id: None,
-    inputs: 2,
-    output: true,
-    procedure: gate_procedure,
-};
-fn gate_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
-    match (&pallets[0], &pallets[1]) {
-        (Some(Pallet::Bool(b)), Some(pallet)) => Ok(if *b { Some(pallet.clone()) } else { None }),
-        (Some(pallet), Some(Pallet::Bool(b))) => Ok(if *b { Some(pallet.clone()) } else { None }),
-        _ => {
-            return Err(format!(
-                "Expected at least one boolean pallet, received {}\n",
-                list_pallets(pallets)
-            ));
+/// Passes through any pallet other than a false boolean pallet
+#[derive(Debug)]
+pub struct Filter;
+impl StationType for Filter {
+    fn id(&self) -> &str {
+        return "filter";
+    }
+    fn alt_id(&self) -> Option<&str> {
+        return Some("X");
+    }
+    fn inputs(&self) -> usize {
+        return 1;
+    }
+    fn pure(&self) -> bool {
+        return true;
+    }
+    fn procedure(&self, station: &mut Station, _: &mut Context) -> Result<Output, String> {
+        let pallets = &station.in_bays;
+        match &pallets[0] {
+            Some(Pallet::Bool(false)) => Ok(Output::Drop),
+            Some(p) => Ok(Output::Emit(p.clone())),
+            _ => {
+                return Err(format!(
+                    "Expected pallet, received {}\n",
+                    list_pallets(pallets)
+                ));
+            }
         }
     }
 }
+pub const FILTER: Filter = Filter;
 
-pub static FILTER: StationType = StationType {
-    id: "filter",
-    alt_id: Some("X"),
-    inputs: 1,
-    output: true,
-    procedure: filter_procedure,
-};
-fn filter_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
-    match &pallets[0] {
-        Some(Pallet::Bool(false)) => Ok(None),
-        Some(p) => Ok(Some(p.clone())),
-        _ => {
-            return Err(format!(
-                "Expected pallet, received {}\n",
-                list_pallets(pallets)
-            ));
+/// Sends a pallet out of its eastern output bays if it is accompanied by a true
+/// boolean pallet, and out of its western output bays otherwise
+#[derive(Debug)]
+pub struct Branch;
+impl StationType for Branch {
+    fn id(&self) -> &str {
+        return "branch";
+    }
+    fn inputs(&self) -> usize {
+        return 2;
+    }
+    fn pure(&self) -> bool {
+        return true;
+    }
+    fn procedure(&self, station: &mut Station, _: &mut Context) -> Result<Output, String> {
+        let pallets = &station.in_bays;
+        match (&pallets[0], &pallets[1]) {
+            (Some(Pallet::Bool(b)), Some(pallet)) | (Some(pallet), Some(Pallet::Bool(b))) => {
+                let side = if *b { Direction::EAST } else { Direction::WEST };
+                Ok(Output::EmitTo(vec![(side, pallet.clone())]))
+            }
+            _ => {
+                return Err(format!(
+                    "Expected at least one boolean pallet, received {}\n",
+                    list_pallets(pallets)
+                ));
+            }
         }
     }
 }
+pub const BRANCH: Branch = Branch;
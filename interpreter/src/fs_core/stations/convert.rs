@@ -0,0 +1,169 @@
+#[cfg(feature = "bigint")]
+use num_traits::{FromPrimitive, ToPrimitive};
+
+use super::list_pallets;
+use crate::*;
+#[cfg(feature = "bigint")]
+use fs_core::integer::*;
+
+/// Error message for a pallet that can't be converted to a type
+fn convert_error(pallet: &Pallet, type_name: &str) -> String {
+    return format!("Can't convert {pallet} to {type_name}");
+}
+
+/// The pallet in a conversion station's only bay
+fn input(pallets: &Vec<Option<Pallet>>) -> Result<&Pallet, String> {
+    match &pallets[0] {
+        Some(pallet) => return Ok(pallet),
+        None => {
+            return Err(format!(
+                "Expected a pallet, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
+
+/// Parses an integer, promoting it to a big integer if it doesn't fit in an
+/// `Int` when the `bigint` feature is enabled
+fn parse_integer(s: &str) -> Option<Pallet> {
+    #[cfg(feature = "bigint")]
+    return s.parse::<BigInt>().ok().map(from_bigint);
+    #[cfg(not(feature = "bigint"))]
+    return s.parse::<i64>().ok().map(Pallet::Int);
+}
+
+/// Truncates a float towards zero, None if it's not finite or out of range
+fn truncate_float(f: f64) -> Option<Pallet> {
+    if !f.is_finite() {
+        return None;
+    }
+    let f = f.trunc();
+    #[cfg(feature = "bigint")]
+    return BigInt::from_f64(f).map(from_bigint);
+    #[cfg(not(feature = "bigint"))]
+    return (f >= i64::MIN as f64 && f < i64::MAX as f64).then_some(Pallet::Int(f as i64));
+}
+
+pub const INT: BasicStation = BasicStation {
+    id: "int",
+    alt_id: None,
+    inputs: 1,
+    output: true,
+    procedure: int_procedure,
+};
+fn int_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    let pallet = input(pallets)?;
+    let result = match pallet {
+        Pallet::Int(_) => Some(pallet.clone()),
+        #[cfg(feature = "bigint")]
+        Pallet::BigInt(_) => Some(pallet.clone()),
+        Pallet::Float(f) => truncate_float(*f),
+        Pallet::String(s) => parse_integer(s.trim()),
+        Pallet::Char(c) => Some(Pallet::Int(*c as i64)),
+        Pallet::Bool(b) => Some(Pallet::Int(*b as i64)),
+        _ => None,
+    };
+    return result
+        .map(Some)
+        .ok_or_else(|| convert_error(pallet, "an integer"));
+}
+
+pub const FLOAT: BasicStation = BasicStation {
+    id: "float",
+    alt_id: None,
+    inputs: 1,
+    output: true,
+    procedure: float_procedure,
+};
+fn float_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    let pallet = input(pallets)?;
+    let result = match pallet {
+        Pallet::Int(i) => Some(*i as f64),
+        #[cfg(feature = "bigint")]
+        Pallet::BigInt(i) => i.to_f64(),
+        Pallet::Float(f) => Some(*f),
+        Pallet::String(s) => s.trim().parse::<f64>().ok(),
+        Pallet::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+        _ => None,
+    };
+    return result
+        .map(|f| Some(Pallet::Float(f)))
+        .ok_or_else(|| convert_error(pallet, "a float"));
+}
+
+pub const STR: BasicStation = BasicStation {
+    id: "str",
+    alt_id: None,
+    inputs: 1,
+    output: true,
+    procedure: str_procedure,
+};
+fn str_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    return Ok(Some(Pallet::String(input(pallets)?.text())));
+}
+
+pub const CHAR: BasicStation = BasicStation {
+    id: "char",
+    alt_id: None,
+    inputs: 1,
+    output: true,
+    procedure: char_procedure,
+};
+fn char_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    let pallet = input(pallets)?;
+    let result = match pallet {
+        Pallet::Int(i) => u32::try_from(*i).ok().and_then(char::from_u32),
+        Pallet::Char(c) => Some(*c),
+        Pallet::String(s) => {
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(c),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    return result
+        .map(|c| Some(Pallet::Char(c)))
+        .ok_or_else(|| convert_error(pallet, "a character"));
+}
+
+pub const BOOL: BasicStation = BasicStation {
+    id: "bool",
+    alt_id: None,
+    inputs: 1,
+    output: true,
+    procedure: bool_procedure,
+};
+fn bool_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    let pallet = input(pallets)?;
+    let result = match pallet {
+        Pallet::Bool(b) => Some(*b),
+        Pallet::String(s) => match s.trim() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        },
+        Pallet::Int(i) => Some(*i != 0),
+        #[cfg(feature = "bigint")]
+        Pallet::BigInt(_) => Some(true),
+        Pallet::Float(f) => Some(*f != 0.0),
+        _ => None,
+    };
+    return result
+        .map(|b| Some(Pallet::Bool(b)))
+        .ok_or_else(|| convert_error(pallet, "a boolean"));
+}
+
+pub const TYPE: BasicStation = BasicStation {
+    id: "type",
+    alt_id: None,
+    inputs: 1,
+    output: true,
+    procedure: type_procedure,
+};
+fn type_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    let name = input(pallets)?.type_name();
+    return Ok(Some(Pallet::String(String::from(name))));
+}
@@ -0,0 +1,142 @@
+use super::{list_pallets, split_pallets};
+use crate::*;
+
+/// Splits a station's pallets into the first map among them, and the rest of
+/// the pallets in bay order
+fn split_map(pallets: &Vec<Option<Pallet>>) -> Option<(&map::Entries, Vec<&Pallet>)> {
+    match split_pallets(pallets, |p| matches!(p, Pallet::Map(_)))? {
+        (Pallet::Map(entries), rest) => return Some((entries, rest)),
+        _ => return None,
+    }
+}
+
+pub const INSERT: BasicStation = BasicStation {
+    id: "insert",
+    alt_id: None,
+    inputs: 3,
+    output: true,
+    procedure: insert_procedure,
+};
+fn insert_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    match split_map(pallets)
+        .as_ref()
+        .map(|(entries, rest)| (entries, rest.as_slice()))
+    {
+        Some((entries, [key, value])) => {
+            let mut entries = entries.to_vec();
+            map::insert(&mut entries, (*key).clone(), (*value).clone())?;
+            return Ok(Some(Pallet::Map(entries)));
+        }
+        _ => {
+            return Err(format!(
+                "Expected map, key and value, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
+
+/// Looks up a key in a map, sending its value out of its eastern output bays if
+/// the key is in the map, and the key out of its western output bays if not
+#[derive(Debug)]
+pub struct Lookup;
+impl StationType for Lookup {
+    fn id(&self) -> &str {
+        return "lookup";
+    }
+    fn inputs(&self) -> usize {
+        return 2;
+    }
+    fn pure(&self) -> bool {
+        return true;
+    }
+    fn procedure(&self, station: &mut Station, _: &mut Context) -> Result<Output, String> {
+        match split_map(&station.in_bays)
+            .as_ref()
+            .map(|(entries, rest)| (entries, rest.as_slice()))
+        {
+            Some((entries, [key])) => match map::get(entries, key) {
+                Some(value) => return Ok(Output::EmitTo(vec![(Direction::EAST, value.clone())])),
+                None if map::is_key(key) => {
+                    return Ok(Output::EmitTo(vec![(Direction::WEST, (*key).clone())]));
+                }
+                None => return Err(map::key_error(key)),
+            },
+            _ => {
+                return Err(format!(
+                    "Expected map and key, received: {}\n",
+                    list_pallets(&station.in_bays)
+                ));
+            }
+        }
+    }
+}
+pub const LOOKUP: Lookup = Lookup;
+
+pub const REMOVE: BasicStation = BasicStation {
+    id: "remove",
+    alt_id: None,
+    inputs: 2,
+    output: true,
+    procedure: remove_procedure,
+};
+fn remove_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    match split_map(pallets)
+        .as_ref()
+        .map(|(entries, rest)| (entries, rest.as_slice()))
+    {
+        Some((entries, [key])) if map::is_key(key) => {
+            let mut entries = entries.to_vec();
+            map::remove(&mut entries, key);
+            return Ok(Some(Pallet::Map(entries)));
+        }
+        Some((_, [key])) => return Err(map::key_error(key)),
+        _ => {
+            return Err(format!(
+                "Expected map and key, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
+
+pub const KEYS: BasicStation = BasicStation {
+    id: "keys",
+    alt_id: None,
+    inputs: 1,
+    output: true,
+    procedure: keys_procedure,
+};
+fn keys_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    match &pallets[0] {
+        Some(Pallet::Map(entries)) => {
+            let keys = entries.iter().map(|(key, _)| key.clone()).collect();
+            return Ok(Some(Pallet::List(keys)));
+        }
+        _ => {
+            return Err(format!(
+                "Expected map pallet, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
+
+pub const SIZE: BasicStation = BasicStation {
+    id: "size",
+    alt_id: None,
+    inputs: 1,
+    output: true,
+    procedure: size_procedure,
+};
+fn size_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    match &pallets[0] {
+        Some(Pallet::Map(entries)) => return Ok(Some(Pallet::Int(entries.len() as i64))),
+        _ => {
+            return Err(format!(
+                "Expected map pallet, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
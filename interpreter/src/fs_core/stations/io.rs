@@ -1,72 +1,74 @@
-use std::io::{stdin, stdout, Write};
+use std::io::Write;
 
 use crate::*;
 
-pub static PRINT: StationType = StationType {
-    id: "print",
-    alt_id: None,
-    inputs: 1,
-    output: false,
-    procedure: print_procedure,
-};
-fn print_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
-    match &pallets[0] {
-        Some(Pallet::Empty) => {}
-        Some(Pallet::Bool(b)) => {
-            if *b {
-                print!("true");
-            } else {
-                print!("false");
-            }
-        }
-        Some(Pallet::Char(c)) => {
-            print!("{c}");
-        }
-        Some(Pallet::String(s)) => {
-            print!("{s}");
-        }
-        Some(Pallet::Int(i)) => {
-            print!("{i}");
-        }
-        Some(Pallet::Float(f)) => {
-            print!("{f}");
-        }
-        None => return Err(String::from("Missing pallet in print")),
+/// Prints a pallet's value to the program's output
+#[derive(Debug)]
+pub struct Print;
+impl StationType for Print {
+    fn id(&self) -> &str {
+        return "print";
+    }
+    fn inputs(&self) -> usize {
+        return 1;
+    }
+    fn procedure(&self, station: &mut Station, ctx: &mut Context) -> Result<Output, String> {
+        print_pallet(&station.in_bays[0], ctx.stdout)?;
+        return Ok(Output::Drop);
     }
-    return Ok(None);
 }
+pub const PRINT: Print = Print;
 
-pub static PRINTLN: StationType = StationType {
-    id: "println",
-    alt_id: None,
-    inputs: 1,
-    output: false,
-    procedure: println_procedure,
-};
-fn println_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
-    print_procedure(pallets)?;
-    println!();
-    return Ok(None);
+/// Writes the value of a pallet to an output stream
+fn print_pallet(pallet: &Option<Pallet>, out: &mut dyn Write) -> Result<(), String> {
+    let result = match pallet {
+        Some(pallet) => write!(out, "{}", pallet.text()),
+        None => return Err(String::from("Missing pallet in print")),
+    };
+    return result.map_err(|e| e.to_string());
 }
 
-pub static READLN: StationType = StationType {
-    id: "readln",
-    alt_id: None,
-    inputs: 1,
-    output: true,
-    procedure: readln_procedure,
-};
-fn readln_procedure(_: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
-    let mut input = String::new();
-    let _ = stdout().flush();
-    match stdin().read_line(&mut input) {
-        Err(e) => return Err(e.to_string()),
-        Ok(_) => {
-            return Ok(Some(Pallet::String(if input.ends_with('\n') {
-                input.strip_suffix('\n').unwrap().to_owned()
-            } else {
-                input
-            })));
+/// Prints a pallet's value to the program's output, followed by a newline
+#[derive(Debug)]
+pub struct Println;
+impl StationType for Println {
+    fn id(&self) -> &str {
+        return "println";
+    }
+    fn inputs(&self) -> usize {
+        return 1;
+    }
+    fn procedure(&self, station: &mut Station, ctx: &mut Context) -> Result<Output, String> {
+        print_pallet(&station.in_bays[0], ctx.stdout)?;
+        writeln!(ctx.stdout).map_err(|e| e.to_string())?;
+        return Ok(Output::Drop);
+    }
+}
+pub const PRINTLN: Println = Println;
+
+/// Reads a line from the program's input
+#[derive(Debug)]
+pub struct Readln;
+impl StationType for Readln {
+    fn id(&self) -> &str {
+        return "readln";
+    }
+    fn inputs(&self) -> usize {
+        return 1;
+    }
+    fn procedure(&self, _: &mut Station, ctx: &mut Context) -> Result<Output, String> {
+        let mut input = String::new();
+        let _ = ctx.stdout.flush();
+        match ctx.stdin.read_line(&mut input) {
+            Err(e) => return Err(e.to_string()),
+            Ok(_) => {
+                return Ok(Output::Emit(Pallet::String(if input.ends_with('\n') {
+                    input.strip_suffix('\n').unwrap().to_owned()
+                } else {
+                    input
+                })));
+            }
         }
     }
 }
+pub const READLN: Readln = Readln;
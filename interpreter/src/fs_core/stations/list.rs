@@ -0,0 +1,253 @@
+use std::cmp::Ordering;
+
+use super::{list_pallets, split_pallets};
+use crate::*;
+use fs_core::integer::*;
+
+/// Converts an integer pallet into an index of a list, if it's in range
+fn list_index(i: i64, len: usize) -> Result<usize, String> {
+    if i < 0 || i as usize >= len {
+        return Err(format!(
+            "Index {i} is out of range for a list of length {len}"
+        ));
+    }
+    return Ok(i as usize);
+}
+
+/// Splits a station's pallets into a list and the rest of the pallets in bay
+/// order
+fn split_list(pallets: &Vec<Option<Pallet>>) -> Option<(&Vec<Pallet>, Vec<&Pallet>)> {
+    match split_pallets(pallets, |p| matches!(p, Pallet::List(_)))? {
+        (Pallet::List(list), rest) => return Some((list, rest)),
+        _ => return None,
+    }
+}
+
+/// Compares two pallets of the same type, None if they can't be ordered
+fn compare_pallets(pallet1: &Pallet, pallet2: &Pallet) -> Option<Ordering> {
+    match (pallet1, pallet2) {
+        (p1, p2) if is_integer(p1) && is_integer(p2) => compare_integers(p1, p2),
+        (Pallet::Float(num1), Pallet::Float(num2)) => Some(num1.total_cmp(num2)),
+        (Pallet::String(s1), Pallet::String(s2)) => Some(s1.cmp(s2)),
+        (Pallet::Char(c1), Pallet::Char(c2)) => Some(c1.cmp(c2)),
+        (Pallet::Bool(b1), Pallet::Bool(b2)) => Some(b1.cmp(b2)),
+        _ => None,
+    }
+}
+
+pub const PUSH: BasicStation = BasicStation {
+    id: "push",
+    alt_id: None,
+    inputs: 2,
+    output: true,
+    procedure: push_procedure,
+};
+fn push_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    match split_list(pallets)
+        .as_ref()
+        .map(|(list, rest)| (list, rest.as_slice()))
+    {
+        Some((list, [pallet])) => {
+            let mut list = list.to_vec();
+            list.push((*pallet).clone());
+            return Ok(Some(Pallet::List(list)));
+        }
+        _ => {
+            return Err(format!(
+                "Expected list and pallet, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
+
+/// Removes the last item of a list, sending the item out of its eastern output
+/// bays and the rest of the list out of its western output bays
+#[derive(Debug)]
+pub struct Pop;
+impl StationType for Pop {
+    fn id(&self) -> &str {
+        return "pop";
+    }
+    fn inputs(&self) -> usize {
+        return 1;
+    }
+    fn pure(&self) -> bool {
+        return true;
+    }
+    fn procedure(&self, station: &mut Station, _: &mut Context) -> Result<Output, String> {
+        match &station.in_bays[0] {
+            Some(Pallet::List(list)) => {
+                let mut list = list.clone();
+                let Some(item) = list.pop() else {
+                    return Err(String::from("Can't pop from an empty list"));
+                };
+                return Ok(Output::EmitTo(vec![
+                    (Direction::EAST, item),
+                    (Direction::WEST, Pallet::List(list)),
+                ]));
+            }
+            _ => {
+                return Err(format!(
+                    "Expected list pallet, received: {}\n",
+                    list_pallets(&station.in_bays)
+                ));
+            }
+        }
+    }
+}
+pub const POP: Pop = Pop;
+
+pub const GET: BasicStation = BasicStation {
+    id: "get",
+    alt_id: None,
+    inputs: 2,
+    output: true,
+    procedure: get_procedure,
+};
+fn get_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    match split_list(pallets)
+        .as_ref()
+        .map(|(list, rest)| (list, rest.as_slice()))
+    {
+        Some((list, [Pallet::Int(i)])) => {
+            return Ok(Some(list[list_index(*i, list.len())?].clone()));
+        }
+        _ => {
+            return Err(format!(
+                "Expected list and integer pallets, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
+
+pub const SET: BasicStation = BasicStation {
+    id: "set",
+    alt_id: None,
+    inputs: 3,
+    output: true,
+    procedure: set_procedure,
+};
+fn set_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    match split_list(pallets)
+        .as_ref()
+        .map(|(list, rest)| (list, rest.as_slice()))
+    {
+        Some((list, [Pallet::Int(i), pallet])) => {
+            let mut list = list.to_vec();
+            let i = list_index(*i, list.len())?;
+            list[i] = (*pallet).clone();
+            return Ok(Some(Pallet::List(list)));
+        }
+        _ => {
+            return Err(format!(
+                "Expected list, integer and pallet, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
+
+pub const LENGTH: BasicStation = BasicStation {
+    id: "len",
+    alt_id: None,
+    inputs: 1,
+    output: true,
+    procedure: length_procedure,
+};
+fn length_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    match &pallets[0] {
+        Some(Pallet::List(list)) => return Ok(Some(Pallet::Int(list.len() as i64))),
+        Some(Pallet::String(s)) => return Ok(Some(Pallet::Int(s.chars().count() as i64))),
+        _ => {
+            return Err(format!(
+                "Expected list or string pallet, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
+
+pub const CONCAT: BasicStation = BasicStation {
+    id: "concat",
+    alt_id: None,
+    inputs: 2,
+    output: true,
+    procedure: concat_procedure,
+};
+fn concat_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    match (&pallets[0], &pallets[1]) {
+        (Some(Pallet::List(list1)), Some(Pallet::List(list2))) => {
+            return Ok(Some(Pallet::List([list1.as_slice(), list2].concat())));
+        }
+        _ => {
+            return Err(format!(
+                "Expected list pallets, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
+
+pub const SLICE: BasicStation = BasicStation {
+    id: "slice",
+    alt_id: None,
+    inputs: 3,
+    output: true,
+    procedure: slice_procedure,
+};
+fn slice_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    match split_list(pallets)
+        .as_ref()
+        .map(|(list, rest)| (list, rest.as_slice()))
+    {
+        Some((list, [Pallet::Int(start), Pallet::Int(end)])) => {
+            if *start < 0 || start > end || *end as usize > list.len() {
+                return Err(format!(
+                    "Slice {start}..{end} is out of range for a list of length {}",
+                    list.len()
+                ));
+            }
+            return Ok(Some(Pallet::List(
+                list[*start as usize..*end as usize].to_vec(),
+            )));
+        }
+        _ => {
+            return Err(format!(
+                "Expected list and two integer pallets, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
+
+pub const SORT: BasicStation = BasicStation {
+    id: "sort",
+    alt_id: None,
+    inputs: 1,
+    output: true,
+    procedure: sort_procedure,
+};
+fn sort_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    let list = match &pallets[0] {
+        Some(Pallet::List(list)) => list,
+        _ => {
+            return Err(format!(
+                "Expected list pallet, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    };
+    if let Some(first) = list.first() {
+        if list.iter().any(|p| compare_pallets(first, p).is_none()) {
+            return Err(format!(
+                "Expected a list of comparable pallets of one type, received: {}",
+                pallets[0].as_ref().unwrap()
+            ));
+        }
+    }
+    let mut list = list.clone();
+    list.sort_by(|p1, p2| compare_pallets(p1, p2).unwrap());
+    return Ok(Some(Pallet::List(list)));
+}
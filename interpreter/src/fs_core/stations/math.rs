@@ -1,7 +1,10 @@
+use std::cmp::Ordering;
+
 use super::list_pallets;
 use crate::*;
+use fs_core::integer::*;
 
-pub static EQUALS: StationType = StationType {
+pub const EQUALS: BasicStation = BasicStation {
     id: "eq",
     alt_id: Some("="),
     inputs: 2,
@@ -9,10 +12,12 @@ pub static EQUALS: StationType = StationType {
     procedure: equals_procedure,
 };
 fn equals_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    // maps keep their entries sorted by key, so maps with the same entries are
+    // equal whatever order they were inserted in
     return Ok(Some(Pallet::Bool(pallets[0] == pallets[1])));
 }
 
-pub static NOT_EQUALS: StationType = StationType {
+pub const NOT_EQUALS: BasicStation = BasicStation {
     id: "ne",
     alt_id: Some("!="),
     inputs: 2,
@@ -23,7 +28,7 @@ fn not_equals_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>,
     return Ok(Some(Pallet::Bool(pallets[0] != pallets[1])));
 }
 
-pub static GREATER_THAN: StationType = StationType {
+pub const GREATER_THAN: BasicStation = BasicStation {
     id: "gt",
     alt_id: Some(">"),
     inputs: 2,
@@ -32,8 +37,9 @@ pub static GREATER_THAN: StationType = StationType {
 };
 fn greater_than_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
     match (&pallets[0], &pallets[1]) {
-        (Some(Pallet::Int(num1)), Some(Pallet::Int(num2))) => {
-            return Ok(Some(Pallet::Bool(num1 > num2)));
+        (Some(p1), Some(p2)) if is_integer(p1) && is_integer(p2) => {
+            let ordering = compare_integers(p1, p2).unwrap();
+            return Ok(Some(Pallet::Bool(ordering == Ordering::Greater)));
         }
         (Some(Pallet::Float(num1)), Some(Pallet::Float(num2))) => {
             return Ok(Some(Pallet::Bool(num1 > num2)));
@@ -50,7 +56,7 @@ fn greater_than_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet
     }
 }
 
-pub static LESS_THAN: StationType = StationType {
+pub const LESS_THAN: BasicStation = BasicStation {
     id: "lt",
     alt_id: Some("<"),
     inputs: 2,
@@ -59,8 +65,9 @@ pub static LESS_THAN: StationType = StationType {
 };
 fn less_than_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
     match (&pallets[0], &pallets[1]) {
-        (Some(Pallet::Int(num1)), Some(Pallet::Int(num2))) => {
-            return Ok(Some(Pallet::Bool(num1 < num2)));
+        (Some(p1), Some(p2)) if is_integer(p1) && is_integer(p2) => {
+            let ordering = compare_integers(p1, p2).unwrap();
+            return Ok(Some(Pallet::Bool(ordering == Ordering::Less)));
         }
         (Some(Pallet::Float(num1)), Some(Pallet::Float(num2))) => {
             return Ok(Some(Pallet::Bool(num1 < num2)));
@@ -77,7 +84,7 @@ fn less_than_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>,
     }
 }
 
-pub static GREATER_THAN_EQUAL: StationType = StationType {
+pub const GREATER_THAN_EQUAL: BasicStation = BasicStation {
     id: "gte",
     alt_id: Some(">="),
     inputs: 2,
@@ -86,8 +93,9 @@ pub static GREATER_THAN_EQUAL: StationType = StationType {
 };
 fn greater_than_equal_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
     match (&pallets[0], &pallets[1]) {
-        (Some(Pallet::Int(num1)), Some(Pallet::Int(num2))) => {
-            return Ok(Some(Pallet::Bool(num1 >= num2)));
+        (Some(p1), Some(p2)) if is_integer(p1) && is_integer(p2) => {
+            let ordering = compare_integers(p1, p2).unwrap();
+            return Ok(Some(Pallet::Bool(ordering != Ordering::Less)));
         }
         (Some(Pallet::Float(num1)), Some(Pallet::Float(num2))) => {
             return Ok(Some(Pallet::Bool(num1 >= num2)));
@@ -104,7 +112,7 @@ fn greater_than_equal_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<
     }
 }
 
-pub static LESS_THAN_EQUAL: StationType = StationType {
+pub const LESS_THAN_EQUAL: BasicStation = BasicStation {
     id: "lte",
     alt_id: Some("<="),
     inputs: 2,
@@ -113,8 +121,9 @@ pub static LESS_THAN_EQUAL: StationType = StationType {
 };
 fn less_than_equal_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
     match (&pallets[0], &pallets[1]) {
-        (Some(Pallet::Int(num1)), Some(Pallet::Int(num2))) => {
-            return Ok(Some(Pallet::Bool(num1 <= num2)));
+        (Some(p1), Some(p2)) if is_integer(p1) && is_integer(p2) => {
+            let ordering = compare_integers(p1, p2).unwrap();
+            return Ok(Some(Pallet::Bool(ordering != Ordering::Greater)));
         }
         (Some(Pallet::Float(num1)), Some(Pallet::Float(num2))) => {
             return Ok(Some(Pallet::Bool(num1 <= num2)));
@@ -131,17 +140,17 @@ fn less_than_equal_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pal
     }
 }
 
-pub static ADD: StationType = StationType {
+pub const ADD: BasicStation = BasicStation {
     id: "add",
     alt_id: Some("+"),
     inputs: 2,
     output: true,
     procedure: add_procedure,
 };
-fn add_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+pub(super) fn add_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
     match (&pallets[0], &pallets[1]) {
-        (Some(Pallet::Int(num1)), Some(Pallet::Int(num2))) => {
-            return Ok(Some(Pallet::Int(num1 + num2)));
+        (Some(p1), Some(p2)) if is_integer(p1) && is_integer(p2) => {
+            return integer_arithmetic(IntOp::Add, p1, p2).map(Some);
         }
         (Some(Pallet::Float(num1)), Some(Pallet::Float(num2))) => {
             return Ok(Some(Pallet::Float(num1 + num2)));
@@ -163,7 +172,7 @@ fn add_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String
     }
 }
 
-pub static SUBTRACT: StationType = StationType {
+pub const SUBTRACT: BasicStation = BasicStation {
     id: "sub",
     alt_id: Some("-"),
     inputs: 2,
@@ -172,8 +181,8 @@ pub static SUBTRACT: StationType = StationType {
 };
 fn subtract_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
     match (&pallets[0], &pallets[1]) {
-        (Some(Pallet::Int(num1)), Some(Pallet::Int(num2))) => {
-            return Ok(Some(Pallet::Int(num1 - num2)));
+        (Some(p1), Some(p2)) if is_integer(p1) && is_integer(p2) => {
+            return integer_arithmetic(IntOp::Subtract, p1, p2).map(Some);
         }
         (Some(Pallet::Float(num1)), Some(Pallet::Float(num2))) => {
             return Ok(Some(Pallet::Float(num1 - num2)));
@@ -187,7 +196,7 @@ fn subtract_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, S
     }
 }
 
-pub static MULTIPLY: StationType = StationType {
+pub const MULTIPLY: BasicStation = BasicStation {
     id: "mult",
     alt_id: Some("*"),
     inputs: 2,
@@ -196,8 +205,8 @@ pub static MULTIPLY: StationType = StationType {
 };
 fn multiply_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
     match (&pallets[0], &pallets[1]) {
-        (Some(Pallet::Int(num1)), Some(Pallet::Int(num2))) => {
-            return Ok(Some(Pallet::Int(num1 * num2)));
+        (Some(p1), Some(p2)) if is_integer(p1) && is_integer(p2) => {
+            return integer_arithmetic(IntOp::Multiply, p1, p2).map(Some);
         }
         (Some(Pallet::Float(num1)), Some(Pallet::Float(num2))) => {
             return Ok(Some(Pallet::Float(num1 * num2)));
@@ -211,7 +220,7 @@ fn multiply_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, S
     }
 }
 
-pub static DIVIDE: StationType = StationType {
+pub const DIVIDE: BasicStation = BasicStation {
     id: "div",
     alt_id: Some("/"),
     inputs: 2,
@@ -220,11 +229,8 @@ pub static DIVIDE: StationType = StationType {
 };
 fn divide_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
     match (&pallets[0], &pallets[1]) {
-        (Some(Pallet::Int(num1)), Some(Pallet::Int(num2))) => {
-            if *num2 == 0 {
-                return Err(String::from("Attempted divide by zero"));
-            }
-            return Ok(Some(Pallet::Int(num1 / num2)));
+        (Some(p1), Some(p2)) if is_integer(p1) && is_integer(p2) => {
+            return integer_arithmetic(IntOp::Divide, p1, p2).map(Some);
         }
         (Some(Pallet::Float(num1)), Some(Pallet::Float(num2))) => {
             if *num2 == 0.0 {
@@ -241,7 +247,7 @@ fn divide_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, Str
     }
 }
 
-pub static MODULO: StationType = StationType {
+pub const MODULO: BasicStation = BasicStation {
     id: "mod",
     alt_id: Some("%"),
     inputs: 2,
@@ -250,11 +256,8 @@ pub static MODULO: StationType = StationType {
 };
 fn modulo_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
     match (&pallets[0], &pallets[1]) {
-        (Some(Pallet::Int(num1)), Some(Pallet::Int(num2))) => {
-            if *num2 == 0 {
-                return Err(String::from("Attempted divide by zero"));
-            }
-            return Ok(Some(Pallet::Int(num1 % num2)));
+        (Some(p1), Some(p2)) if is_integer(p1) && is_integer(p2) => {
+            return integer_arithmetic(IntOp::Modulo, p1, p2).map(Some);
         }
         (Some(Pallet::Float(num1)), Some(Pallet::Float(num2))) => {
             if *num2 == 0.0 {
@@ -271,7 +274,7 @@ fn modulo_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, Str
     }
 }
 
-pub static INCREMENT: StationType = StationType {
+pub const INCREMENT: BasicStation = BasicStation {
     id: "inc",
     alt_id: Some("++"),
     inputs: 1,
@@ -280,8 +283,8 @@ pub static INCREMENT: StationType = StationType {
 };
 fn increment_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
     match &pallets[0] {
-        Some(Pallet::Int(num)) => {
-            return Ok(Some(Pallet::Int(num + 1)));
+        Some(p) if is_integer(p) => {
+            return integer_arithmetic(IntOp::Add, p, &Pallet::Int(1)).map(Some);
         }
         Some(Pallet::Float(num)) => {
             return Ok(Some(Pallet::Float(num + 1.0)));
@@ -295,7 +298,7 @@ fn increment_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>,
     }
 }
 
-pub static DECREMENT: StationType = StationType {
+pub const DECREMENT: BasicStation = BasicStation {
     id: "dec",
     alt_id: Some("--"),
     inputs: 1,
@@ -304,8 +307,8 @@ pub static DECREMENT: StationType = StationType {
 };
 fn decrement_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
     match &pallets[0] {
-        Some(Pallet::Int(num)) => {
-            return Ok(Some(Pallet::Int(num - 1)));
+        Some(p) if is_integer(p) => {
+            return integer_arithmetic(IntOp::Subtract, p, &Pallet::Int(1)).map(Some);
         }
         Some(Pallet::Float(num)) => {
             return Ok(Some(Pallet::Float(num - 1.0)));
@@ -319,7 +322,7 @@ fn decrement_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>,
     }
 }
 
-pub static AND: StationType = StationType {
+pub const AND: BasicStation = BasicStation {
     id: "and",
     alt_id: None,
     inputs: 2,
@@ -338,7 +341,7 @@ fn and_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String
     }
 }
 
-pub static OR: StationType = StationType {
+pub const OR: BasicStation = BasicStation {
     id: "or",
     alt_id: None,
     inputs: 2,
@@ -357,7 +360,7 @@ fn or_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String>
     }
 }
 
-pub static NOT: StationType = StationType {
+pub const NOT: BasicStation = BasicStation {
     id: "not",
     alt_id: Some("!"),
     inputs: 1,
@@ -0,0 +1,91 @@
+use super::*;
+
+/// Counts every pallet it receives, emitting the running total
+#[derive(Debug)]
+pub struct Count;
+impl StationType for Count {
+    fn id(&self) -> &str {
+        return "count";
+    }
+    fn inputs(&self) -> usize {
+        return 1;
+    }
+    fn pure(&self) -> bool {
+        return true;
+    }
+    fn procedure(&self, station: &mut Station, _: &mut Context) -> Result<Output, String> {
+        let received = station.in_bays.iter().flatten().count() as i64;
+        let count = match &station.state {
+            Some(Pallet::Int(count)) => count + received,
+            _ => received,
+        };
+        station.state = Some(Pallet::Int(count));
+        return Ok(Output::Emit(Pallet::Int(count)));
+    }
+}
+pub const COUNT: Count = Count;
+
+/// Adds every pallet it receives to a running sum, emitting the sum
+#[derive(Debug)]
+pub struct Accumulate;
+impl StationType for Accumulate {
+    fn id(&self) -> &str {
+        return "acc";
+    }
+    fn inputs(&self) -> usize {
+        return 1;
+    }
+    fn pure(&self) -> bool {
+        return true;
+    }
+    fn procedure(&self, station: &mut Station, _: &mut Context) -> Result<Output, String> {
+        let mut sum = station.state.take();
+        for pallet in station.in_bays.iter().flatten() {
+            sum = match sum {
+                Some(sum) => math::add_procedure(&vec![Some(sum), Some(pallet.clone())])?,
+                None => Some(pallet.clone()),
+            };
+        }
+        station.state = sum.clone();
+        match sum {
+            Some(sum) => Ok(Output::Emit(sum)),
+            None => Err(String::from("Expected pallet, received none")),
+        }
+    }
+}
+pub const ACCUMULATE: Accumulate = Accumulate;
+
+/// Remembers the pallet received in its first bay, and emits the last one
+/// remembered whenever a pallet arrives at its second bay
+#[derive(Debug)]
+pub struct Latch;
+impl StationType for Latch {
+    fn id(&self) -> &str {
+        return "latch";
+    }
+    fn inputs(&self) -> usize {
+        return 1;
+    }
+    fn pure(&self) -> bool {
+        return true;
+    }
+    fn procedure(&self, station: &mut Station, _: &mut Context) -> Result<Output, String> {
+        if station.in_bays.len() != 2 {
+            return Err(format!(
+                "Expected a value bay and a trigger bay, found {} bay(s)",
+                station.in_bays.len()
+            ));
+        }
+        if let Some(p) = &station.in_bays[0] {
+            station.state = Some(p.clone());
+        }
+        if station.in_bays[1].is_none() {
+            return Ok(Output::Drop);
+        }
+        match &station.state {
+            Some(p) => Ok(Output::Emit(p.clone())),
+            None => Ok(Output::Drop),
+        }
+    }
+}
+pub const LATCH: Latch = Latch;
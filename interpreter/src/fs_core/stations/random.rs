@@ -0,0 +1,121 @@
+use super::list_pallets;
+use crate::*;
+
+/// Small pseudorandom number generator (SplitMix64). Every random station keeps
+/// its own generator in its state, seeded from the configured seed and the
+/// station's location, so a run with the same seed produces the same numbers
+struct Rng(u64);
+impl Rng {
+    /// The station's generator, or a freshly seeded one on its first trigger
+    fn of(station: &Station, config: &Config) -> Self {
+        if let Some(Pallet::Int(state)) = station.state {
+            return Self(state as u64);
+        }
+        let pos = station.loc.pos;
+        let loc = ((pos.line as u64) << 32) | pos.col as u64;
+        return Self(config.seed ^ loc.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    }
+
+    /// Stores the generator in the station's state for its next trigger
+    fn save(self, station: &mut Station) {
+        station.state = Some(Pallet::Int(self.0 as i64));
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        return z ^ (z >> 31);
+    }
+
+    /// Float in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        return (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+    }
+
+    /// Integer in [0, n), for n up to 2^64
+    fn below(&mut self, n: u128) -> u128 {
+        return (self.next_u64() as u128 * n) >> 64;
+    }
+}
+
+/// Emits a random float in [0, 1) whenever it receives a pallet
+#[derive(Debug)]
+pub struct Rand;
+impl StationType for Rand {
+    fn id(&self) -> &str {
+        return "rand";
+    }
+    fn inputs(&self) -> usize {
+        return 1;
+    }
+    fn procedure(&self, station: &mut Station, ctx: &mut Context) -> Result<Output, String> {
+        let mut rng = Rng::of(station, ctx.config);
+        let value = rng.next_f64();
+        rng.save(station);
+        return Ok(Output::Emit(Pallet::Float(value)));
+    }
+}
+pub const RAND: Rand = Rand;
+
+/// Emits a random integer between the integers in its two bays, inclusive, in
+/// either order
+#[derive(Debug)]
+pub struct RandInt;
+impl StationType for RandInt {
+    fn id(&self) -> &str {
+        return "randint";
+    }
+    fn inputs(&self) -> usize {
+        return 2;
+    }
+    fn procedure(&self, station: &mut Station, ctx: &mut Context) -> Result<Output, String> {
+        let (low, high) = match (&station.in_bays[0], &station.in_bays[1]) {
+            (Some(Pallet::Int(a)), Some(Pallet::Int(b))) => (*a.min(b), *a.max(b)),
+            _ => {
+                return Err(format!(
+                    "Expected integer pallets, received: {}\n",
+                    list_pallets(&station.in_bays)
+                ));
+            }
+        };
+        let mut rng = Rng::of(station, ctx.config);
+        let width = (high as i128 - low as i128 + 1) as u128;
+        let value = (low as i128 + rng.below(width) as i128) as i64;
+        rng.save(station);
+        return Ok(Output::Emit(Pallet::Int(value)));
+    }
+}
+pub const RANDINT: RandInt = RandInt;
+
+/// Emits the string it receives with its characters in a random order
+#[derive(Debug)]
+pub struct Shuffle;
+impl StationType for Shuffle {
+    fn id(&self) -> &str {
+        return "shuffle";
+    }
+    fn inputs(&self) -> usize {
+        return 1;
+    }
+    fn procedure(&self, station: &mut Station, ctx: &mut Context) -> Result<Output, String> {
+        let mut chars: Vec<char> = match &station.in_bays[0] {
+            Some(Pallet::String(s)) => s.chars().collect(),
+            _ => {
+                return Err(format!(
+                    "Expected string pallet, received: {}\n",
+                    list_pallets(&station.in_bays)
+                ));
+            }
+        };
+        let mut rng = Rng::of(station, ctx.config);
+        for i in (1..chars.len()).rev() {
+            let j = rng.below(i as u128 + 1) as usize;
+            chars.swap(i, j);
+        }
+        rng.save(station);
+        return Ok(Output::Emit(Pallet::String(chars.into_iter().collect())));
+    }
+}
+pub const SHUFFLE: Shuffle = Shuffle;
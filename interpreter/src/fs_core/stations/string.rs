@@ -0,0 +1,301 @@
+use super::{list_pallets, split_pallets};
+use crate::*;
+
+/// Converts an integer pallet into an index of a string's characters, if it's
+/// in range
+fn string_index(i: i64, len: usize) -> Result<usize, String> {
+    if i < 0 || i as usize >= len {
+        return Err(format!(
+            "Index {i} is out of range for a string of length {len}"
+        ));
+    }
+    return Ok(i as usize);
+}
+
+/// Splits a station's pallets into a string and the rest of the pallets in bay
+/// order
+fn split_string(pallets: &Vec<Option<Pallet>>) -> Option<(&String, Vec<&Pallet>)> {
+    match split_pallets(pallets, |p| matches!(p, Pallet::String(_)))? {
+        (Pallet::String(s), rest) => return Some((s, rest)),
+        _ => return None,
+    }
+}
+
+/// The text of a string or character pallet, used to search strings
+fn pattern(pallet: Option<&Pallet>) -> Option<String> {
+    match pallet {
+        Some(Pallet::String(s)) => return Some(s.clone()),
+        Some(Pallet::Char(c)) => return Some(c.to_string()),
+        _ => return None,
+    }
+}
+
+/// Counts the characters before a byte offset in a string
+fn char_index(s: &str, byte: usize) -> i64 {
+    return s[..byte].chars().count() as i64;
+}
+
+pub const CHAR_AT: BasicStation = BasicStation {
+    id: "charat",
+    alt_id: None,
+    inputs: 2,
+    output: true,
+    procedure: char_at_procedure,
+};
+fn char_at_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    match split_string(pallets)
+        .as_ref()
+        .map(|(s, rest)| (s, rest.as_slice()))
+    {
+        Some((s, [Pallet::Int(i)])) => {
+            let len = s.chars().count();
+            let c = s.chars().nth(string_index(*i, len)?).unwrap();
+            return Ok(Some(Pallet::Char(c)));
+        }
+        _ => {
+            return Err(format!(
+                "Expected string and integer pallets, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
+
+pub const SUBSTRING: BasicStation = BasicStation {
+    id: "substr",
+    alt_id: None,
+    inputs: 3,
+    output: true,
+    procedure: substring_procedure,
+};
+fn substring_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    match split_string(pallets)
+        .as_ref()
+        .map(|(s, rest)| (s, rest.as_slice()))
+    {
+        Some((s, [Pallet::Int(start), Pallet::Int(end)])) => {
+            let len = s.chars().count();
+            if *start < 0 || start > end || *end as usize > len {
+                return Err(format!(
+                    "Substring {start}..{end} is out of range for a string of length {len}"
+                ));
+            }
+            let substring = s
+                .chars()
+                .skip(*start as usize)
+                .take((end - start) as usize)
+                .collect();
+            return Ok(Some(Pallet::String(substring)));
+        }
+        _ => {
+            return Err(format!(
+                "Expected string and two integer pallets, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
+
+pub const SPLIT: BasicStation = BasicStation {
+    id: "split",
+    alt_id: None,
+    inputs: 2,
+    output: true,
+    procedure: split_procedure,
+};
+fn split_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    match (&pallets[0], pattern(pallets[1].as_ref())) {
+        // An empty separator splits a string into its characters
+        (Some(Pallet::String(s)), Some(separator)) if separator.is_empty() => {
+            let parts = s.chars().map(|c| Pallet::String(c.to_string())).collect();
+            return Ok(Some(Pallet::List(parts)));
+        }
+        (Some(Pallet::String(s)), Some(separator)) => {
+            let parts = s
+                .split(separator.as_str())
+                .map(|part| Pallet::String(part.to_owned()))
+                .collect();
+            return Ok(Some(Pallet::List(parts)));
+        }
+        _ => {
+            return Err(format!(
+                "Expected string and separator pallets, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
+
+pub const JOIN: BasicStation = BasicStation {
+    id: "join",
+    alt_id: None,
+    inputs: 2,
+    output: true,
+    procedure: join_procedure,
+};
+fn join_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    if let Some((Pallet::List(list), rest)) =
+        split_pallets(pallets, |p| matches!(p, Pallet::List(_)))
+    {
+        if let Some(separator) = pattern(rest.first().copied()) {
+            let items: Vec<String> = list.iter().map(|item| item.text()).collect();
+            return Ok(Some(Pallet::String(items.join(&separator))));
+        }
+    }
+    return Err(format!(
+        "Expected list and separator pallets, received: {}\n",
+        list_pallets(pallets)
+    ));
+}
+
+pub const UPPER: BasicStation = BasicStation {
+    id: "upper",
+    alt_id: None,
+    inputs: 1,
+    output: true,
+    procedure: upper_procedure,
+};
+fn upper_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    match &pallets[0] {
+        Some(Pallet::String(s)) => return Ok(Some(Pallet::String(s.to_uppercase()))),
+        _ => {
+            return Err(format!(
+                "Expected string pallet, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
+
+pub const LOWER: BasicStation = BasicStation {
+    id: "lower",
+    alt_id: None,
+    inputs: 1,
+    output: true,
+    procedure: lower_procedure,
+};
+fn lower_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    match &pallets[0] {
+        Some(Pallet::String(s)) => return Ok(Some(Pallet::String(s.to_lowercase()))),
+        _ => {
+            return Err(format!(
+                "Expected string pallet, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
+
+pub const TRIM: BasicStation = BasicStation {
+    id: "trim",
+    alt_id: None,
+    inputs: 1,
+    output: true,
+    procedure: trim_procedure,
+};
+fn trim_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    match &pallets[0] {
+        Some(Pallet::String(s)) => return Ok(Some(Pallet::String(s.trim().to_owned()))),
+        _ => {
+            return Err(format!(
+                "Expected string pallet, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
+
+pub const CONTAINS: BasicStation = BasicStation {
+    id: "contains",
+    alt_id: None,
+    inputs: 2,
+    output: true,
+    procedure: contains_procedure,
+};
+fn contains_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    match (&pallets[0], pattern(pallets[1].as_ref())) {
+        (Some(Pallet::String(s)), Some(pattern)) => {
+            return Ok(Some(Pallet::Bool(s.contains(pattern.as_str()))));
+        }
+        _ => {
+            return Err(format!(
+                "Expected string and pattern pallets, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
+
+pub const STARTS_WITH: BasicStation = BasicStation {
+    id: "startswith",
+    alt_id: None,
+    inputs: 2,
+    output: true,
+    procedure: starts_with_procedure,
+};
+fn starts_with_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    match (&pallets[0], pattern(pallets[1].as_ref())) {
+        (Some(Pallet::String(s)), Some(prefix)) => {
+            return Ok(Some(Pallet::Bool(s.starts_with(prefix.as_str()))));
+        }
+        _ => {
+            return Err(format!(
+                "Expected string and prefix pallets, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
+
+pub const REPLACE: BasicStation = BasicStation {
+    id: "replace",
+    alt_id: None,
+    inputs: 3,
+    output: true,
+    procedure: replace_procedure,
+};
+fn replace_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    match (
+        &pallets[0],
+        pattern(pallets[1].as_ref()),
+        pattern(pallets[2].as_ref()),
+    ) {
+        (Some(Pallet::String(_)), Some(from), Some(_)) if from.is_empty() => {
+            return Err(String::from("Can't replace an empty pattern"));
+        }
+        (Some(Pallet::String(s)), Some(from), Some(to)) => {
+            return Ok(Some(Pallet::String(s.replace(from.as_str(), &to))));
+        }
+        _ => {
+            return Err(format!(
+                "Expected string, pattern and replacement pallets, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
+
+pub const FIND: BasicStation = BasicStation {
+    id: "find",
+    alt_id: None,
+    inputs: 2,
+    output: true,
+    procedure: find_procedure,
+};
+fn find_procedure(pallets: &Vec<Option<Pallet>>) -> Result<Option<Pallet>, String> {
+    match (&pallets[0], pattern(pallets[1].as_ref())) {
+        (Some(Pallet::String(s)), Some(pattern)) => {
+            let index = match s.find(pattern.as_str()) {
+                Some(byte) => char_index(s, byte),
+                None => -1,
+            };
+            return Ok(Some(Pallet::Int(index)));
+        }
+        _ => {
+            return Err(format!(
+                "Expected string and pattern pallets, received: {}\n",
+                list_pallets(pallets)
+            ));
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use super::list_pallets;
+use crate::*;
+
+/// Blocks for the number of milliseconds it receives, then passes the pallet on
+#[derive(Debug)]
+pub struct Sleep;
+impl StationType for Sleep {
+    fn id(&self) -> &str {
+        return "sleep";
+    }
+    fn inputs(&self) -> usize {
+        return 1;
+    }
+    fn procedure(&self, station: &mut Station, ctx: &mut Context) -> Result<Output, String> {
+        match &station.in_bays[0] {
+            Some(Pallet::Int(ms)) if *ms >= 0 => {
+                let _ = ctx.stdout.flush();
+                ctx.config.clock.sleep(Duration::from_millis(*ms as u64));
+                return Ok(Output::Emit(Pallet::Int(*ms)));
+            }
+            Some(Pallet::Int(ms)) => {
+                return Err(format!("Can't sleep for a negative duration ({ms}ms)"));
+            }
+            _ => {
+                return Err(format!(
+                    "Expected integer pallet, received: {}\n",
+                    list_pallets(&station.in_bays)
+                ));
+            }
+        }
+    }
+}
+pub const SLEEP: Sleep = Sleep;
+
+/// Emits the milliseconds passed since the program started running whenever it
+/// receives a pallet, which never go backwards
+#[derive(Debug)]
+pub struct Elapsed;
+impl StationType for Elapsed {
+    fn id(&self) -> &str {
+        return "clock";
+    }
+    fn inputs(&self) -> usize {
+        return 1;
+    }
+    fn procedure(&self, _: &mut Station, ctx: &mut Context) -> Result<Output, String> {
+        let ms = ctx.elapsed().as_millis();
+        return Ok(Output::Emit(Pallet::Int(ms as i64)));
+    }
+}
+pub const CLOCK: Elapsed = Elapsed;
+
+/// Emits the current Unix timestamp, in seconds, whenever it receives a pallet
+#[derive(Debug)]
+pub struct Now;
+impl StationType for Now {
+    fn id(&self) -> &str {
+        return "now";
+    }
+    fn inputs(&self) -> usize {
+        return 1;
+    }
+    fn procedure(&self, _: &mut Station, ctx: &mut Context) -> Result<Output, String> {
+        let secs = ctx.config.clock.now().as_secs();
+        return Ok(Output::Emit(Pallet::Int(secs as i64)));
+    }
+}
+pub const NOW: Now = Now;
@@ -0,0 +1,333 @@
+use std::{fmt, ops::Range, time::Duration};
+
+use crate::*;
+
+/// A program lowered to a flat list of instructions, one per station. Every
+/// input bay of the program is a slot in a single array, and the belts leaving
+/// each station are a range of a shared fan-out table, so running it needs no
+/// per-station allocation or string lookups
+#[derive(Debug, Clone)]
+pub struct Ir {
+    /// Instructions of each station, by station index
+    pub instructions: Vec<Instruction>,
+    /// Belts leaving every station, each station's are a contiguous range
+    pub fanout: Vec<Target>,
+    /// Total number of input bay slots
+    pub slots: usize,
+    /// Stations in their initial state, for their locations and the procedures
+    /// of `Call` instructions
+    stations: Vec<Station>,
+}
+
+/// Compiled form of a station
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    /// Slots of the station's input bays
+    pub bays: Range<usize>,
+    /// Entries of the fan-out table for the station's output bays
+    pub fanout: Range<usize>,
+}
+
+/// Destination of a belt in the fan-out table
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Target {
+    /// Slot of the input bay the belt leads into
+    pub slot: usize,
+    /// Index of the station the belt leads into
+    pub station: usize,
+    /// Side of the origin station the belt leaves from
+    pub side: Direction,
+    /// Number of steps a pallet spends on the belt before being delivered
+    pub delay: usize,
+}
+
+/// Pallet travelling to a slot
+#[derive(Debug)]
+struct Moving {
+    target: usize,
+    delay: usize,
+    pallet: Pallet,
+}
+
+impl Ir {
+    /// Lowers a program's stations, the configuration decides how long pallets
+    /// take to travel along belts
+    pub fn compile(stations: &[Station], config: &Config) -> Self {
+        let mut first_slots = Vec::with_capacity(stations.len());
+        let mut slots = 0;
+        for station in stations.iter() {
+            first_slots.push(slots);
+            slots += station.in_bays.len();
+        }
+
+        let mut instructions = Vec::with_capacity(stations.len());
+        let mut fanout = Vec::new();
+        for (i, station) in stations.iter().enumerate() {
+            let fanout_start = fanout.len();
+            for out_bay in station.out_bays.iter() {
+                let (dest, bay) = out_bay.dest;
+                fanout.push(Target {
+                    slot: first_slots[dest] + bay,
+                    station: dest,
+                    side: out_bay.side,
+                    delay: out_bay.delay(config),
+                });
+            }
+            instructions.push(Instruction {
+                opcode: station.logic.opcode(),
+                bays: first_slots[i]..(first_slots[i] + station.in_bays.len()),
+                fanout: fanout_start..fanout.len(),
+            });
+        }
+        debug!(
+            config,
+            2,
+            "Compiled {} instructions, {} slots, {} fan-out entries",
+            instructions.len(),
+            slots,
+            fanout.len()
+        );
+        return Self {
+            instructions,
+            fanout,
+            slots,
+            stations: stations.to_vec(),
+        };
+    }
+
+    /// Runs the program to completion, with the same steps as
+    /// `runtime::execute`. Returns the number of steps in the program
+    pub fn execute(&self, ctx: &mut Context) -> Result<usize, Error> {
+        ctx.start = ctx.config.clock.elapsed();
+        ctx.offset = Duration::ZERO;
+        let mut stations = self.stations.clone();
+        let mut slots: Vec<Option<Pallet>> = vec![None; self.slots];
+        let mut moving: Vec<Moving> = Vec::new();
+        let mut arriving: Vec<Moving> = Vec::new();
+        // stations that received a pallet this step, the only ones that can
+        // have become triggered
+        let mut touched: Vec<usize> = Vec::new();
+        let mut is_touched = vec![false; stations.len()];
+
+        for (i, station) in stations.iter().enumerate() {
+            if let Some(p) = station.logic.spawn() {
+                self.send(i, None, p, &mut moving);
+            }
+        }
+
+        let mut step_count = 0;
+        while !moving.is_empty() {
+            for m in std::mem::take(&mut moving) {
+                if m.delay == 0 {
+                    arriving.push(m);
+                } else {
+                    moving.push(Moving {
+                        delay: m.delay - 1,
+                        ..m
+                    });
+                }
+            }
+            for m in arriving.drain(..) {
+                let target = &self.fanout[m.target];
+                debug!(
+                    ctx.config,
+                    3,
+                    " - pallet moved to slot {} of #{} ({})",
+                    target.slot,
+                    target.station,
+                    m.pallet
+                );
+                slots[target.slot] = Some(m.pallet);
+                if !is_touched[target.station] {
+                    is_touched[target.station] = true;
+                    touched.push(target.station);
+                }
+            }
+
+            touched.sort_unstable();
+            for i in touched.drain(..) {
+                is_touched[i] = false;
+                let instruction = &self.instructions[i];
+                let bays = &mut slots[instruction.bays.clone()];
+                let logic = stations[i].logic.clone();
+                if !logic.triggered(bays) {
+                    continue;
+                }
+                debug!(
+                    ctx.config,
+                    3,
+                    " - Procedure triggered on #{i} ({})",
+                    logic.id()
+                );
+                let output = match instruction.opcode {
+                    Opcode::Joint => match bays.iter_mut().find_map(Option::take) {
+                        Some(p) => Ok(Output::Emit(p)),
+                        None => Err(String::from("Expected pallet, received none")),
+                    },
+                    Opcode::Assign => match &stations[i].config {
+                        Some(p) => Ok(Output::Emit(p.clone())),
+                        None => Err(String::from("Assign station is missing its literal")),
+                    },
+                    Opcode::Exit => Ok(Output::Halt),
+                    Opcode::Call => {
+                        let station = &mut stations[i];
+                        for (in_bay, slot) in station.in_bays.iter_mut().zip(bays.iter_mut()) {
+                            *in_bay = slot.take();
+                        }
+                        let output = logic.procedure(station, ctx);
+                        station.clear_in_bays();
+                        output
+                    }
+                };
+                for slot in bays.iter_mut() {
+                    *slot = None;
+                }
+                match output {
+                    Ok(Output::Emit(p)) => self.send(i, None, p, &mut moving),
+                    Ok(Output::EmitTo(pallets)) => {
+                        for (side, p) in pallets {
+                            self.send(i, Some(side), p, &mut moving);
+                        }
+                    }
+                    Ok(Output::Drop) => {}
+                    Ok(Output::Halt) => {
+                        debug!(ctx.config, 2, "Halted by #{i}");
+                        return Ok(step_count);
+                    }
+                    Err(msg) => return Err(Error::new(RuntimeError, stations[i].loc, msg)),
+                }
+            }
+            debug!(ctx.config, 3, "Step {} completed", step_count);
+            step_count += 1;
+        }
+        debug!(ctx.config, 2, "No remaining moving pallets");
+        return Ok(step_count);
+    }
+
+    /// Sends a pallet along the belts leaving a station, or only those leaving
+    /// one side of it
+    fn send(&self, station: usize, side: Option<Direction>, p: Pallet, moving: &mut Vec<Moving>) {
+        let fanout = self.instructions[station].fanout.clone();
+        for target in fanout {
+            if side.is_none_or(|side| side == self.fanout[target].side) {
+                moving.push(Moving {
+                    target,
+                    delay: self.fanout[target].delay,
+                    pallet: p.clone(),
+                });
+            }
+        }
+    }
+}
+impl fmt::Display for Ir {
+    /// Lists every instruction with its station, slots and fan-out targets. Each
+    /// target is written as the slot it leads into, followed by the side of the
+    /// station it leaves from and its delay if it has one
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "; {} instructions, {} slots, {} fan-out entries",
+            self.instructions.len(),
+            self.slots,
+            self.fanout.len()
+        )?;
+        for (i, (instruction, station)) in self.instructions.iter().zip(&self.stations).enumerate()
+        {
+            let opcode = match instruction.opcode {
+                Opcode::Joint => String::from("joint"),
+                Opcode::Assign => match &station.config {
+                    Some(p) => format!("assign {p}"),
+                    None => String::from("assign"),
+                },
+                Opcode::Exit => String::from("exit"),
+                Opcode::Call => format!("call [{}]", station.logic.id()),
+            };
+            let targets: Vec<String> = self.fanout[instruction.fanout.clone()]
+                .iter()
+                .map(|target| match target.delay {
+                    0 => format!("@{} {}", target.slot, target.side),
+                    delay => format!("@{} {} +{delay}", target.slot, target.side),
+                })
+                .collect();
+            writeln!(
+                f,
+                "{:>5}  {:<10} {:<24} slots {:<8} -> {}",
+                format!("#{i}"),
+                station.loc.pos.to_string(),
+                opcode,
+                format!("{:?}", instruction.bays),
+                targets.join(", ")
+            )?;
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(src: &str, config: Config) -> (String, usize, usize) {
+        let program = Program::new(src).unwrap();
+        let mut runtime_output: Vec<u8> = Vec::new();
+        let mut ir_output: Vec<u8> = Vec::new();
+        let mut interpreter = Interpreter::new()
+            .config(config.clone())
+            .stdout(&mut runtime_output);
+        let runtime_steps = runtime::execute(
+            program.stations().clone(),
+            &mut interpreter.context(),
+            &mut (),
+        )
+        .unwrap();
+        drop(interpreter);
+        let ir = Ir::compile(program.stations(), &config);
+        let mut interpreter = Interpreter::new().config(config).stdout(&mut ir_output);
+        let ir_steps = ir.execute(&mut interpreter.context()).unwrap();
+        drop(interpreter);
+        assert_eq!(runtime_output, ir_output);
+        return (
+            String::from_utf8(ir_output).unwrap(),
+            runtime_steps,
+            ir_steps,
+        );
+    }
+
+    #[test]
+    fn test_ir_matches_runtime() {
+        for src in [
+            include_str!("../../examples/fizzbuzz.factory"),
+            include_str!("../../examples/fibonacci.factory"),
+            include_str!("../../examples/hello_world_pretty.factory"),
+        ] {
+            for physical_belts in [false, true] {
+                let config = Config::default().with_physical_belts(physical_belts);
+                let (output, runtime_steps, ir_steps) = run(src, config);
+                assert!(!output.is_empty());
+                assert_eq!(runtime_steps, ir_steps);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ir_halt() {
+        let (output, runtime_steps, ir_steps) = run(
+            "[start]═─{\"a\"}═─[println]\n   ╚─[exit]",
+            Config::default(),
+        );
+        assert_eq!(output, "");
+        assert_eq!((runtime_steps, ir_steps), (0, 0));
+    }
+
+    #[test]
+    fn test_ir_dump() {
+        let program = Program::new("[start]═─{\"hi\"}═─[println]").unwrap();
+        let dump = Ir::compile(program.stations(), &Config::default()).to_string();
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines[0], "; 3 instructions, 2 slots, 2 fan-out entries");
+        assert!(lines[1].starts_with("   #0  1:0        call [start]"));
+        assert!(lines[2].contains("assign Pallet<s:\"hi\">"));
+        assert!(lines[2].ends_with("-> @1 east"));
+    }
+}
@@ -1,65 +1,64 @@
-pub static mut COLOR_OUTPUT: bool = false;
-pub static mut DEBUG_LEVEL: u8 = 0;
+use std::{cmp::min, sync::Arc};
 
-use std::{cmp::min, time::Instant};
+use serde::{Deserialize, Serialize};
 
+pub mod animate;
+pub mod config;
+pub mod coverage;
+pub mod debugger;
 pub mod error;
 pub mod fs_core;
+pub mod ir;
 pub mod macros;
+pub mod namespace;
+pub mod optimizer;
 pub mod preprocessor;
+pub mod profile;
+pub mod program;
 pub mod runtime;
+pub mod session;
+pub mod snapshot;
+pub mod trace;
+pub mod transpiler;
 
+pub use animate::Animator;
+pub use config::{Clock, Config, Logger, SystemClock, TerminalLogger, VirtualClock};
+pub use coverage::Coverage;
+pub use debugger::Debugger;
 use error::{Error, ErrorType::*};
 use fs_core::{stations::STATION_TYPES, *};
+pub use ir::Ir;
+pub use namespace::{FnStation, Namespace};
+pub use profile::{Profiler, SortKey};
+pub use program::{Interpreter, Program, Report};
+pub use runtime::{Event, MovingPallet, Observer, Runtime};
+pub use session::Session;
+pub use snapshot::Snapshot;
+pub use trace::TraceWriter;
 
-pub type Namespace = Vec<&'static StationType>;
-
-pub fn run(src: &str, print_benchmark: bool) -> Result<(), Error> {
-    let start_time = Instant::now();
-
-    debug!(2, "Preprocessing...");
-    let (mut stations, start_i, assign_table) = preprocessor::process(src, &STATION_TYPES)?;
-    let runtime_start_time = Instant::now();
-    debug!(2, "Starting");
-    let step_count = runtime::execute(&mut stations, start_i, &assign_table)?;
-
-    if print_benchmark {
-        let end_time = Instant::now();
-        let preprocess_duration: f64 =
-            ((runtime_start_time - start_time).as_nanos() as f64) / 1000000000f64;
-        let runtime_duration: f64 =
-            ((end_time - runtime_start_time).as_nanos() as f64) / 1000000000f64;
-        let total_duration: f64 = ((end_time - start_time).as_nanos() as f64) / 1000000000f64;
-
-        let avg_step_duration =
-            ((end_time - runtime_start_time).as_nanos() as f64) / ((1000 * step_count) as f64);
-
-        println!("\n======Benchmark======");
-        println!(" steps      {}", step_count);
-        println!(" avg step   {:.2}ms", avg_step_duration);
-        println!();
-        println!(" preprocess {:.5}s", preprocess_duration);
-        println!(" runtime    {:.5}s", runtime_duration);
-        println!(" total      {:.5}s", total_duration);
-        println!("=====================");
-    }
-
-    Ok(())
+/// Preprocesses and runs FactoryScript source code using the process's standard
+/// streams
+pub fn run(src: &str, config: &Config) -> Result<Report, Error> {
+    return Interpreter::new().config(config.clone()).run_src(src);
 }
 
 /// Instance of a station
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Station {
     /// Location of the station in source code
     pub loc: SourceSpan,
     /// Station functionality and type information
-    pub logic: &'static StationType,
+    pub logic: Arc<dyn StationType>,
     /// Modifiers duh
     pub modifiers: StationModifiers,
+    /// Station specific configuration, such as the literal of an assign station
+    pub config: Option<Pallet>,
+    /// Memory of stateful stations, kept between triggers
+    pub state: Option<Pallet>,
     /// Queues for each input bay
     pub in_bays: Vec<Option<Pallet>>,
-    /// Map of each output bay connection in the form (station_index, in_bay_index)
-    pub out_bays: Vec<(usize, usize)>,
+    /// Connections from each output bay to the input bays of other stations
+    pub out_bays: Vec<OutBay>,
 }
 impl Station {
     pub fn new(
@@ -68,16 +67,16 @@ impl Station {
         modifiers: StationModifiers,
         ns: &Namespace,
     ) -> Result<Self, Error> {
-        for station_type in ns {
-            if station_type.has_id(identifier) {
-                return Ok(Self {
-                    loc,
-                    logic: station_type,
-                    modifiers,
-                    in_bays: Vec::new(),
-                    out_bays: Vec::new(),
-                });
-            }
+        if let Some(station_type) = ns.get(identifier) {
+            return Ok(Self {
+                loc,
+                logic: station_type.clone(),
+                modifiers,
+                config: None,
+                state: None,
+                in_bays: Vec::new(),
+                out_bays: Vec::new(),
+            });
         }
         return Err(Error::new(
             IdentifierError,
@@ -95,6 +94,38 @@ impl Station {
     }
 }
 
+/// Connection from one of a station's output bays to another station's input bay
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutBay {
+    /// Destination in the form (station_index, in_bay_index)
+    pub dest: (usize, usize),
+    /// Side of the origin station that the conveyor belt leaves from
+    pub side: Direction,
+    /// Cells of the conveyor belt, in order from the origin station to the
+    /// destination
+    pub path: Vec<SourcePos>,
+    /// Steps pallets spend on the belt on top of travelling along it, for belts
+    /// the optimizer merged through the stations it removed
+    pub extra_steps: usize,
+}
+impl OutBay {
+    /// Number of cells in the conveyor belt
+    pub fn length(&self) -> usize {
+        return self.path.len();
+    }
+
+    /// Number of steps a pallet spends on the belt before being delivered. With
+    /// physical belts, every cell of the belt takes a step to travel along
+    pub fn delay(&self, config: &Config) -> usize {
+        let travel = if config.physical_belts {
+            self.length().saturating_sub(1)
+        } else {
+            0
+        };
+        return travel + self.extra_steps;
+    }
+}
+
 /// Struct for holding the modifiers of an instance of a station
 #[derive(Debug, Clone, Copy)]
 pub struct StationModifiers {
@@ -103,14 +134,16 @@ pub struct StationModifiers {
     /// Which direction the precedence starts with
     pub priority: Direction,
 }
-impl StationModifiers {
+impl Default for StationModifiers {
     /// Default modifiers for a station
-    pub fn default() -> Self {
+    fn default() -> Self {
         Self {
             reverse: false,
             priority: Direction::NORTH,
         }
     }
+}
+impl StationModifiers {
     /// toggles the reverse direction modifier
     pub fn reverse(self) -> Self {
         Self {
@@ -127,11 +160,9 @@ impl StationModifiers {
     }
 }
 
-/// Trait to describe a type that references a location in the source code
-
 /// Defines the position of a span of characters in the source code, used for
 /// syntax parsing and error reporting
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
 pub struct SourcePos {
     /// line number
     pub line: usize,
@@ -149,9 +180,9 @@ impl SourcePos {
         SourceSpan::new(*self, len)
     }
 }
-impl Into<SourceSpan> for SourcePos {
-    fn into(self) -> SourceSpan {
-        SourceSpan::new(self, 1)
+impl From<SourcePos> for SourceSpan {
+    fn from(pos: SourcePos) -> Self {
+        SourceSpan::new(pos, 1)
     }
 }
 impl std::fmt::Display for SourcePos {
@@ -162,7 +193,7 @@ impl std::fmt::Display for SourcePos {
 
 /// Defines the position of a span of characters in the source code, used for
 /// syntax parsing and error reporting
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
 pub struct SourceSpan {
     /// line number
     pub pos: SourcePos,
@@ -192,7 +223,7 @@ impl std::fmt::Display for SourceSpan {
 }
 
 /// Helper for the cardinal directions
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Direction {
     NORTH,
     SOUTH,
@@ -1,57 +1,18 @@
+/// Logs a debug message through a config's logger, if the config's debug level
+/// is at least the message's level
 #[macro_export]
 macro_rules! debug {
-    ($level:literal, $msg:literal, $($args:expr),*) => {
-		unsafe {
-			if $level <= DEBUG_LEVEL {
-				if $level > 1 && COLOR_OUTPUT {
-					print!("\x1b[90m");
-					print!($msg, $($args),*);
-					println!("\x1b[0m");
-				} else {
-					println!($msg, $($args),*);
-				}
-			}
-		}
-    };
-    ($level:literal, $msg:literal) => {
-		unsafe {
-			if $level <= DEBUG_LEVEL {
-				if $level > 1 && COLOR_OUTPUT {
-					print!("\x1b[90m");
-					print!($msg);
-					println!("\x1b[0m");
-				} else {
-					println!($msg);
-				}
-			}
+    ($config:expr, $level:literal, $($args:tt)*) => {
+		if $level <= $config.debug_level {
+			$config.logger.debug($level, &format!($($args)*));
 		}
     };
 }
 
+/// Logs an error message through a config's logger
 #[macro_export]
 macro_rules! print_err {
-    ($msg:literal, $($args:expr),*) => {
-		unsafe {
-			if COLOR_OUTPUT {
-				print!("\x1b[31m");
-				print!($msg, $($args),*);
-				println!("\x1b[0m");
-			} else {
-				print!("ERROR! ");
-				println!($msg, $($args),*);
-			}
-		}
-    };
-    ($msg:literal) => {
-		unsafe {
-			if COLOR_OUTPUT {
-				print!("\x1b[31m");
-				print!($msg);
-				println!("\x1b[0m");
-			} else {
-				print!("ERROR! ");
-				println!($msg);
-			}
-		}
+    ($config:expr, $($args:tt)*) => {
+		$config.logger.error(&format!($($args)*))
     };
 }
@@ -0,0 +1,189 @@
+use std::sync::Arc;
+
+use crate::*;
+
+/// Registry of the station types available to a program. Station types
+/// registered later shadow earlier ones that share an identifier
+#[derive(Debug, Clone, Default)]
+pub struct Namespace {
+    station_types: Vec<Arc<dyn StationType>>,
+}
+impl Namespace {
+    /// Creates an empty namespace
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    /// Creates a namespace containing all of the builtin station types
+    pub fn builtin() -> Self {
+        return STATION_TYPES.clone();
+    }
+
+    /// Adds a station type, shadowing any station types with the same identifiers
+    pub fn register<T: StationType + 'static>(&mut self, station_type: T) -> &mut Self {
+        self.station_types.push(Arc::new(station_type));
+        return self;
+    }
+
+    /// Removes every station type with an identifier, returns whether any were
+    /// removed
+    pub fn disable(&mut self, id: &str) -> bool {
+        let len = self.station_types.len();
+        self.station_types
+            .retain(|station_type| !station_type.has_id(id));
+        return self.station_types.len() != len;
+    }
+
+    /// Finds the station type with an identifier
+    pub fn get(&self, id: &str) -> Option<&Arc<dyn StationType>> {
+        return self
+            .station_types
+            .iter()
+            .rev()
+            .find(|station_type| station_type.has_id(id));
+    }
+
+    /// Iterates over every station type, including shadowed ones
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn StationType>> {
+        return self.station_types.iter();
+    }
+}
+
+/// Procedure of a station type defined by a closure
+type FnProcedure = dyn Fn(&mut Station, &mut Context) -> Result<Output, String> + Send + Sync;
+
+/// Station type defined at runtime by the host, with a closure as its procedure.
+/// The closure can capture host state, as long as it is thread safe
+pub struct FnStation {
+    id: String,
+    alt_id: Option<String>,
+    inputs: usize,
+    procedure: Box<FnProcedure>,
+}
+impl FnStation {
+    /// Creates a station type with an identifier, a minimum number of inputs, and
+    /// a procedure
+    pub fn new<F>(id: &str, inputs: usize, procedure: F) -> Self
+    where
+        F: Fn(&mut Station, &mut Context) -> Result<Output, String> + Send + Sync + 'static,
+    {
+        return Self {
+            id: id.to_owned(),
+            alt_id: None,
+            inputs,
+            procedure: Box::new(procedure),
+        };
+    }
+
+    /// Sets the alternate identifier
+    pub fn with_alt_id(self, alt_id: &str) -> Self {
+        return Self {
+            alt_id: Some(alt_id.to_owned()),
+            ..self
+        };
+    }
+}
+impl StationType for FnStation {
+    fn id(&self) -> &str {
+        return &self.id;
+    }
+    fn alt_id(&self) -> Option<&str> {
+        return self.alt_id.as_deref();
+    }
+    fn inputs(&self) -> usize {
+        return self.inputs;
+    }
+    fn procedure(&self, station: &mut Station, ctx: &mut Context) -> Result<Output, String> {
+        return (self.procedure)(station, ctx);
+    }
+}
+impl std::fmt::Debug for FnStation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnStation")
+            .field("id", &self.id)
+            .field("alt_id", &self.alt_id)
+            .field("inputs", &self.inputs)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[test]
+    fn test_namespace_builtin() {
+        let ns = Namespace::builtin();
+        assert_eq!(ns.get("start").unwrap().id(), "start");
+        assert_eq!(ns.get("+").unwrap().id(), "add");
+        assert!(ns.get("metric").is_none());
+    }
+
+    #[test]
+    fn test_namespace_shadowing() {
+        let mut ns = Namespace::builtin();
+        ns.register(FnStation::new("print", 1, |_, _| Ok(Output::Drop)).with_alt_id("p"));
+        assert!(ns.get("print").unwrap().has_id("p"));
+        assert!(ns.get("println").unwrap().alt_id().is_none());
+    }
+
+    #[test]
+    fn test_namespace_disable() {
+        let mut ns = Namespace::builtin();
+        assert!(ns.disable("readln"));
+        assert!(!ns.disable("readln"));
+        assert!(ns.get("readln").is_none());
+        assert!(ns.disable("X"));
+        assert!(ns.get("filter").is_none());
+    }
+
+    #[test]
+    fn test_host_station() {
+        let metrics = Arc::new(Mutex::new(Vec::new()));
+        let mut ns = Namespace::builtin();
+        let captured = metrics.clone();
+        ns.register(FnStation::new("metric", 1, move |station, _| {
+            captured
+                .lock()
+                .unwrap()
+                .push(station.in_bays[0].clone().unwrap());
+            return Ok(Output::Drop);
+        }));
+        let program = Program::with_namespace("[start]═─{7}═─[metric]", &ns).unwrap();
+        Interpreter::new().run(&program).unwrap();
+        assert_eq!(*metrics.lock().unwrap(), vec![Pallet::Int(7)]);
+    }
+
+    #[test]
+    fn test_host_spawner() {
+        #[derive(Debug)]
+        struct Source;
+        impl StationType for Source {
+            fn id(&self) -> &str {
+                return "source";
+            }
+            fn inputs(&self) -> usize {
+                return 0;
+            }
+            fn spawn(&self) -> Option<Pallet> {
+                return Some(Pallet::Int(3));
+            }
+            fn procedure(&self, _: &mut Station, _: &mut Context) -> Result<Output, String> {
+                return Ok(Output::Drop);
+            }
+        }
+        let mut ns = Namespace::builtin();
+        ns.register(Source);
+        let program = Program::with_namespace("[source]═─[println]\n[start]", &ns).unwrap();
+        let mut output: Vec<u8> = Vec::new();
+        Interpreter::new()
+            .stdout(&mut output)
+            .run(&program)
+            .unwrap();
+        assert_eq!(output, b"3\n");
+        let err = Program::with_namespace("[source]═─[println]", &ns).unwrap_err();
+        assert_eq!(err.msg, "Unable to locate start station");
+    }
+}
@@ -0,0 +1,291 @@
+use std::{io, time::Duration};
+
+use crate::*;
+
+/// Rewrites a program's stations into an equivalent program with fewer of them:
+/// chains of joints are collapsed into longer belts, stations that only ever
+/// receive literals are folded into assign stations, and stations no pallet can
+/// reach are removed. Belts merged through removed stations keep the steps
+/// pallets spent passing through them, so the optimized program produces the
+/// same output in the same number of steps
+pub fn optimize(mut stations: Vec<Station>, config: &Config) -> Vec<Station> {
+    let count = stations.len();
+    while collapse_joint(&mut stations, config) || fold_constant(&mut stations, config) {}
+    let stations = remove_unreachable(stations);
+    debug!(
+        config,
+        2,
+        "Optimized {} stations down to {}",
+        count,
+        stations.len()
+    );
+    return stations;
+}
+
+/// Belt leading into a station, as (origin station, output bay, input bay)
+type Belt = (usize, usize, usize);
+
+/// Belts leading into each station, by station index
+fn incoming_belts(stations: &[Station]) -> Vec<Vec<Belt>> {
+    let mut incoming = vec![Vec::new(); stations.len()];
+    for (i, station) in stations.iter().enumerate() {
+        for (out_i, out_bay) in station.out_bays.iter().enumerate() {
+            incoming[out_bay.dest.0].push((i, out_i, out_bay.dest.1));
+        }
+    }
+    return incoming;
+}
+
+/// Joins two belts through the station between them, which pallets take a step
+/// to pass through
+fn merge_belts(first: &OutBay, second: &OutBay, config: &Config) -> OutBay {
+    let mut merged = OutBay {
+        dest: second.dest,
+        side: first.side,
+        path: [first.path.as_slice(), second.path.as_slice()].concat(),
+        extra_steps: 0,
+    };
+    merged.extra_steps = first.delay(config) + 1 + second.delay(config) - merged.delay(config);
+    return merged;
+}
+
+/// Bypasses a joint fed by a single belt, connecting the station before it
+/// straight to the stations after it. Only joints whose destination bays aren't
+/// fed by anything else are bypassed, so pallets can't arrive in a different
+/// order. Returns whether a joint was bypassed
+fn collapse_joint(stations: &mut [Station], config: &Config) -> bool {
+    let incoming = incoming_belts(stations);
+    for j in 0..stations.len() {
+        if stations[j].logic.opcode() != Opcode::Joint
+            || stations[j].out_bays.is_empty()
+            || incoming[j].len() != 1
+        {
+            continue;
+        }
+        let (origin, out_i, _) = incoming[j][0];
+        let exclusive = stations[j].out_bays.iter().all(|out_bay| {
+            let (dest, bay) = out_bay.dest;
+            return dest != j && incoming[dest].iter().filter(|b| b.2 == bay).count() == 1;
+        });
+        if origin == j || !exclusive {
+            continue;
+        }
+        debug!(config, 3, " - bypassing joint #{j}");
+        let into_joint = stations[origin].out_bays[out_i].clone();
+        let merged: Vec<OutBay> = stations[j]
+            .out_bays
+            .drain(..)
+            .map(|out_bay| merge_belts(&into_joint, &out_bay, config))
+            .collect();
+        stations[origin].out_bays.splice(out_i..=out_i, merged);
+        return true;
+    }
+    return false;
+}
+
+/// Replaces a pure station with an assign station of its result, if all of its
+/// pallets come from assign stations that fire exactly once, fed by the start
+/// station, and arrive together. The new assign station is fed straight from
+/// the start station and fires in the same step. Returns whether a station was
+/// folded
+fn fold_constant(stations: &mut [Station], config: &Config) -> bool {
+    let incoming = incoming_belts(stations);
+    'stations: for s in 0..stations.len() {
+        let logic = stations[s].logic.clone();
+        if !logic.pure() || logic.opcode() != Opcode::Call || incoming[s].is_empty() {
+            continue;
+        }
+        let mut bays: Vec<Option<Pallet>> = vec![None; stations[s].in_bays.len()];
+        // spawning station, the belt it feeds the assign stations from, and the
+        // step the literals arrive in
+        let mut source: Option<(usize, usize, usize)> = None;
+        for &(a, out_i, bay) in incoming[s].iter() {
+            let literal = match &stations[a].config {
+                Some(p) if stations[a].logic.opcode() == Opcode::Assign => p,
+                _ => continue 'stations,
+            };
+            if bays[bay].is_some() || incoming[a].len() != 1 {
+                continue 'stations;
+            }
+            let (spawner, spawner_out_i, _) = incoming[a][0];
+            let spawner_logic = &stations[spawner].logic;
+            if spawner_logic.inputs() != 0 || spawner_logic.spawn().is_none() {
+                continue 'stations;
+            }
+            let arrival = stations[spawner].out_bays[spawner_out_i].delay(config)
+                + 1
+                + stations[a].out_bays[out_i].delay(config);
+            match source {
+                Some((_, _, step)) if step != arrival => continue 'stations,
+                _ => source = Some((spawner, spawner_out_i, arrival)),
+            }
+            bays[bay] = Some(literal.clone());
+        }
+        let Some((spawner, spawner_out_i, arrival)) = source else {
+            continue;
+        };
+        if !logic.triggered(&bays) {
+            continue;
+        }
+
+        // pure stations never use the streams
+        let mut stdin = io::empty();
+        let mut stdout = io::sink();
+        let mut stderr = io::sink();
+        let mut ctx = Context {
+            config,
+            stdin: &mut stdin,
+            stdout: &mut stdout,
+            stderr: &mut stderr,
+            start: Duration::ZERO,
+            offset: Duration::ZERO,
+        };
+        let mut probe = stations[s].clone();
+        probe.in_bays = bays;
+        let Ok(Output::Emit(result)) = logic.procedure(&mut probe, &mut ctx) else {
+            continue;
+        };
+        debug!(config, 3, " - folding #{s} into {}", result);
+
+        let side = stations[spawner].out_bays[spawner_out_i].side;
+        let mut removed: Vec<(usize, usize)> = incoming[s].iter().map(|b| (b.0, b.1)).collect();
+        removed.sort_unstable_by(|a, b| b.cmp(a));
+        let mut unused = Vec::new();
+        for (a, out_i) in removed {
+            stations[a].out_bays.remove(out_i);
+            // an assign station that fed nothing else fires without effect, and
+            // its pallet arrives before the new one will
+            if stations[a].out_bays.is_empty() {
+                unused.push((incoming[a][0].0, incoming[a][0].1));
+            }
+        }
+        unused.sort_unstable_by(|a, b| b.cmp(a));
+        for (from, out_i) in unused {
+            stations[from].out_bays.remove(out_i);
+        }
+        stations[s].logic = STATION_TYPES.get("assign").unwrap().clone();
+        stations[s].config = Some(result);
+        stations[s].state = None;
+        stations[s].in_bays = vec![None];
+        stations[spawner].out_bays.push(OutBay {
+            dest: (s, 0),
+            side,
+            path: Vec::new(),
+            extra_steps: arrival,
+        });
+        return true;
+    }
+    return false;
+}
+
+/// Removes the stations no pallet can reach from a spawning station
+fn remove_unreachable(stations: Vec<Station>) -> Vec<Station> {
+    let mut reachable = vec![false; stations.len()];
+    let mut queue: Vec<usize> = (0..stations.len())
+        .filter(|i| stations[*i].logic.spawn().is_some())
+        .collect();
+    for i in queue.iter() {
+        reachable[*i] = true;
+    }
+    while let Some(i) = queue.pop() {
+        for out_bay in stations[i].out_bays.iter() {
+            if !reachable[out_bay.dest.0] {
+                reachable[out_bay.dest.0] = true;
+                queue.push(out_bay.dest.0);
+            }
+        }
+    }
+
+    let mut new_indices = vec![0; stations.len()];
+    let mut count = 0;
+    for i in 0..stations.len() {
+        new_indices[i] = count;
+        if reachable[i] {
+            count += 1;
+        }
+    }
+    return stations
+        .into_iter()
+        .zip(reachable)
+        .filter(|(_, reachable)| *reachable)
+        .map(|(mut station, _)| {
+            for out_bay in station.out_bays.iter_mut() {
+                out_bay.dest.0 = new_indices[out_bay.dest.0];
+            }
+            return station;
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs a program's stations as they are and optimized, asserting both
+    /// give the same output in the same number of steps. Returns the output and
+    /// the number of stations after optimizing
+    fn compare(src: &str, config: Config) -> (String, usize) {
+        let program = Program::new(src).unwrap();
+        let optimized = optimize(program.stations().clone(), &config);
+        let mut results = Vec::new();
+        for stations in [program.stations().clone(), optimized.clone()] {
+            let mut output: Vec<u8> = Vec::new();
+            let mut interpreter = Interpreter::new()
+                .config(config.clone())
+                .stdout(&mut output);
+            let steps = runtime::execute(stations, &mut interpreter.context(), &mut ()).unwrap();
+            drop(interpreter);
+            results.push((String::from_utf8(output).unwrap(), steps));
+        }
+        assert_eq!(results[0], results[1]);
+        return (results.remove(0).0, optimized.len());
+    }
+
+    #[test]
+    fn test_collapse_joints() {
+        let src = "[start]═─{\"hi\"}═─[]═─[]═──[]═─[println]";
+        for physical_belts in [false, true] {
+            let config = Config::default().with_physical_belts(physical_belts);
+            assert_eq!(compare(src, config), (String::from("hi\n"), 3));
+        }
+    }
+
+    #[test]
+    fn test_fold_constants() {
+        let src = "\
+[start]═─{2}═─[*]═─[+]═─[println]
+   ║╚─{3}═────┘     │
+   └─{4}═───────────┘";
+        // [*] is folded, but not [+] as its pallets arrive in different steps
+        assert_eq!(compare(src, Config::default()), (String::from("10\n"), 5));
+        // with physical belts neither are, the literals travel different distances
+        let config = Config::default().with_physical_belts(true);
+        assert_eq!(compare(src, config), (String::from("10\n"), 7));
+    }
+
+    #[test]
+    fn test_keep_runtime_errors() {
+        let program = Program::new("[start]═─{0}═─[/]═─[println]\n   ╚─{1}═─────┘").unwrap();
+        let optimized = optimize(program.stations().clone(), &Config::default());
+        assert_eq!(optimized.len(), program.stations().len());
+    }
+
+    #[test]
+    fn test_remove_unreachable() {
+        let src = "[start]═─{\"hi\"}═─[println]\n{1}═─[println]";
+        assert_eq!(compare(src, Config::default()), (String::from("hi\n"), 3));
+    }
+
+    #[test]
+    fn test_optimize_examples() {
+        for src in [
+            include_str!("../../examples/fizzbuzz.factory"),
+            include_str!("../../examples/fibonacci.factory"),
+            include_str!("../../examples/hello_world_pretty.factory"),
+        ] {
+            for physical_belts in [false, true] {
+                compare(src, Config::default().with_physical_belts(physical_belts));
+            }
+        }
+    }
+}
@@ -4,12 +4,15 @@ use fs_core::*;
 /// Given a starting position around a station, check if it is an input bay and
 /// if it is find the origin of the conveyor belt
 ///
-/// Returns an optional tuple of the origin position if it is an input bay
+/// Returns an optional tuple of the origin position, the side of the origin
+/// station that the belt leaves from, and the cells of the belt in order from the
+/// origin, if it is an input bay
 pub fn follow_belt(
     map: &Vec<Vec<char>>,
     visited_map: &mut Vec<Vec<bool>>,
     start: (SourcePos, Direction),
-) -> Result<Option<SourcePos>, Error> {
+    config: &Config,
+) -> Result<Option<(SourcePos, Direction, Vec<SourcePos>)>, Error> {
     let mut pos = start.0;
     let mut facing = start.1;
     let mut c = map[pos.line][pos.col];
@@ -44,7 +47,7 @@ pub fn follow_belt(
         }
     }
 
-    debug!(4, "      probing starting at {}", pos);
+    debug!(config, 4, "      probing starting at {}", pos);
     loop {
         if BELT_CHARS.contains(c) {
             visited.push(pos);
@@ -87,7 +90,10 @@ pub fn follow_belt(
                 "Dangling belt, expected station out bay",
             ));
         }
-        debug!(4, "       - moved to {}, now facing {}", pos, facing);
+        debug!(
+            config,
+            4, "       - moved to {}, now facing {}", pos, facing
+        );
 
         // moving to the next char
         match facing {
@@ -118,11 +124,12 @@ pub fn follow_belt(
         }
         // if the last character was a double belt, we reached the origin
         if DOUBLE_BELT_CHARS.contains(c) {
-            debug!(4, "       - path ended at {}", pos);
-            for pos in visited {
+            debug!(config, 4, "       - path ended at {}", pos);
+            for pos in visited.iter() {
                 visited_map[pos.line][pos.col] = true;
             }
-            return Ok(Some(pos));
+            visited.reverse();
+            return Ok(Some((pos, !facing, visited)));
         }
         // moving
         c = map[pos.line][pos.col];
@@ -9,26 +9,37 @@ mod tests;
 pub fn parse_conveyor_belts(
     char_map: &Vec<Vec<char>>,
     stations: &mut Vec<Station>,
+    config: &Config,
 ) -> Result<(), Error> {
     let mut visited_map: Vec<Vec<bool>> = Vec::new();
     for line in char_map {
         visited_map.push(line.iter().map(|_| false).collect());
     }
-    debug!(2, "Parsing conveyor belts");
+    debug!(config, 2, "Parsing conveyor belts");
     for i in 0..stations.len() {
-        debug!(3, " - #{i} {}", stations[i].logic.id);
+        debug!(config, 3, " - #{i} {}", stations[i].logic.id());
         // get neighbors
         let neighbors = get_neighbors(char_map, &stations[i]);
         for neighbor in neighbors {
             // check if neighbors originate from a station
-            if let Some(origin_pos) =
-                belt_follower::follow_belt(char_map, &mut visited_map, neighbor)?
+            if let Some((origin_pos, side, path)) =
+                belt_follower::follow_belt(char_map, &mut visited_map, neighbor, config)?
             {
                 if let Some(origin_i) = get_station_at(stations, origin_pos) {
-                    debug!(3, "   - bay {} from #{origin_i}", stations[i].in_bays.len());
+                    debug!(
+                        config,
+                        3,
+                        "   - bay {} from #{origin_i} ({side})",
+                        stations[i].in_bays.len()
+                    );
                     let in_bay_index = stations[i].in_bays.len();
                     stations[i].in_bays.push(None);
-                    stations[origin_i].out_bays.push((i, in_bay_index));
+                    stations[origin_i].out_bays.push(OutBay {
+                        dest: (i, in_bay_index),
+                        side,
+                        path,
+                        extra_steps: 0,
+                    });
                 } else {
                     return Err(Error::new(
                         SyntaxError,
@@ -57,15 +68,11 @@ pub fn parse_conveyor_belts(
 
 /// Returns the station located at the specified position, if there is one
 pub fn get_station_at(stations: &Vec<Station>, pos: SourcePos) -> Option<usize> {
-    for i in 0..stations.len() {
-        if stations[i].loc.pos.line == pos.line
-            && stations[i].loc.pos.col <= pos.col
-            && stations[i].loc.pos.col + stations[i].loc.len > pos.col
-        {
-            return Some(i);
-        }
-    }
-    return None;
+    return stations.iter().position(|station| {
+        station.loc.pos.line == pos.line
+            && station.loc.pos.col <= pos.col
+            && station.loc.pos.col + station.loc.len > pos.col
+    });
 }
 
 /// Gets the neighboring location of a specific station in order of highest priority
@@ -192,10 +192,31 @@ fn test_follow_belt() {
     ];
     let mut visited_map = make_visited_map(&map);
     assert_eq!(
-        follow_belt(&map, &mut visited_map, (SourcePos::zero(), Direction::EAST))
-            .ok()
-            .unwrap(),
-        Some(SourcePos::new(1, 0))
+        follow_belt(
+            &map,
+            &mut visited_map,
+            (SourcePos::zero(), Direction::EAST),
+            &Config::default()
+        )
+        .ok()
+        .unwrap(),
+        Some((
+            SourcePos::new(1, 0),
+            Direction::SOUTH,
+            vec![
+                SourcePos::new(2, 0),
+                SourcePos::new(2, 1),
+                SourcePos::new(1, 1),
+                SourcePos::new(1, 2),
+                SourcePos::new(2, 2),
+                SourcePos::new(2, 3),
+                SourcePos::new(1, 3),
+                SourcePos::new(0, 3),
+                SourcePos::new(0, 2),
+                SourcePos::new(0, 1),
+                SourcePos::new(0, 0),
+            ]
+        ))
     );
     assert_eq!(
         visited_map,
@@ -209,11 +230,13 @@ fn test_follow_belt() {
         follow_belt(
             &map,
             &mut visited_map,
-            (SourcePos::new(0, 3), Direction::EAST)
+            (SourcePos::new(0, 3), Direction::EAST),
+            &Config::default()
         )
         .ok()
-        .unwrap(),
-        Some(SourcePos::new(1, 0))
+        .unwrap()
+        .map(|(pos, side, path)| (pos, side, path.len())),
+        Some((SourcePos::new(1, 0), Direction::SOUTH, 8))
     );
 }
 
@@ -221,12 +244,19 @@ fn test_follow_belt() {
 fn test_follow_belt_dangling() {
     let map = vec![vec!['─', '┐'], vec![' ', '─']];
     let mut visited_map = make_visited_map(&map);
-    assert!(follow_belt(&map, &mut visited_map, (SourcePos::zero(), Direction::EAST)).is_err());
+    assert!(follow_belt(
+        &map,
+        &mut visited_map,
+        (SourcePos::zero(), Direction::EAST),
+        &Config::default()
+    )
+    .is_err());
     assert_eq!(visited_map, make_visited_map(&map));
     assert!(follow_belt(
         &map,
         &mut visited_map,
-        (SourcePos::new(1, 1), Direction::WEST)
+        (SourcePos::new(1, 1), Direction::WEST),
+        &Config::default()
     )
     .is_err());
     assert_eq!(visited_map, make_visited_map(&map));
@@ -236,12 +266,19 @@ fn test_follow_belt_dangling() {
 fn test_follow_belt_out_of_bounds() {
     let map = vec![vec!['─', '┐']];
     let mut visited_map = vec![vec![false, false]];
-    assert!(follow_belt(&map, &mut visited_map, (SourcePos::zero(), Direction::EAST)).is_err());
+    assert!(follow_belt(
+        &map,
+        &mut visited_map,
+        (SourcePos::zero(), Direction::EAST),
+        &Config::default()
+    )
+    .is_err());
     assert_eq!(visited_map, vec![vec![false, false]]);
     assert!(follow_belt(
         &map,
         &mut visited_map,
-        (SourcePos::new(0, 1), Direction::EAST)
+        (SourcePos::new(0, 1), Direction::EAST),
+        &Config::default()
     )
     .is_err());
     assert_eq!(visited_map, vec![vec![false, false]]);
@@ -254,7 +291,8 @@ fn test_follow_belt_none() {
     assert!(follow_belt(
         &map,
         &mut visited_map,
-        (SourcePos::zero(), Direction::NORTH)
+        (SourcePos::zero(), Direction::NORTH),
+        &Config::default()
     )
     .ok()
     .unwrap()
@@ -1,22 +1,15 @@
-use std::collections::HashMap;
-
 pub mod conveyor_belt_parser;
 pub mod station_parser;
 
 use crate::*;
-use fs_core::Pallet;
 
 #[cfg(test)]
 mod tests;
 
 /// Preprocesses a source string, validating the syntax and grammar
 ///
-/// Returns a tuple containing a vector of stations and the assignment table, which
-/// store the index of every assign station and its corresponding assign value
-pub fn process<'a>(
-    src: &str,
-    ns: &Namespace,
-) -> Result<(Vec<Station>, usize, HashMap<usize, Pallet>), Error> {
+/// Returns a vector of the stations, connected by their conveyor belts
+pub fn process(src: &str, ns: &Namespace, config: &Config) -> Result<Vec<Station>, Error> {
     // generating 2d vector layout of source code
     let mut char_map: Vec<Vec<char>> = Vec::new();
     let mut n_chars = 0;
@@ -30,15 +23,14 @@ pub fn process<'a>(
     }
 
     // station discovery
-    debug!(3, "Discovering stations");
-    let (mut stations, assign_table) = station_parser::parse_stations(&char_map, ns)?;
-    debug!(3, "Found {} stations", stations.len());
+    debug!(config, 3, "Discovering stations");
+    let mut stations = station_parser::parse_stations(&char_map, ns, config)?;
+    debug!(config, 3, "Found {} stations", stations.len());
 
-    // getting start station's index
-    let mut start_i: usize = 0;
+    // checking for exactly one start station
     let mut found_start = false;
     for i in 0..stations.len() {
-        if stations[i].logic.id == "start" {
+        if stations[i].logic.is_entry() {
             if found_start {
                 return Err(Error::new(
                     SyntaxError,
@@ -46,9 +38,7 @@ pub fn process<'a>(
                     "Found multiple start stations",
                 ));
             }
-            start_i = i;
             found_start = true;
-            break;
         }
     }
     if !found_start {
@@ -60,8 +50,8 @@ pub fn process<'a>(
     }
 
     // parsing conveyor belt connections
-    conveyor_belt_parser::parse_conveyor_belts(&char_map, &mut stations)?;
+    conveyor_belt_parser::parse_conveyor_belts(&char_map, &mut stations, config)?;
 
-    debug!(2, "Finished preprocessing");
-    Ok((stations, start_i, assign_table))
+    debug!(config, 2, "Finished preprocessing");
+    Ok(stations)
 }
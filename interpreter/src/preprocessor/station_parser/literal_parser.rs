@@ -20,7 +20,54 @@ pub fn parse_assign_literal(s: &String, loc: SourceSpan) -> Result<Pallet, Error
         _ => {}
     }
 
-    if s.starts_with('"') {
+    if s.starts_with('[') {
+        // list literal
+        if !s.ends_with(']') {
+            return Err(Error::new(SyntaxError, loc, "Unclosed list literal"));
+        }
+        let inner = s[1..(s.len() - 1)].trim();
+        if inner.is_empty() {
+            return Ok(Pallet::List(Vec::new()));
+        } else if inner == ":" {
+            return Ok(Pallet::Map(Vec::new()));
+        }
+        let items = split_items(inner, ',', loc)?;
+        if split_items(&items[0], ':', loc)?.len() == 1 {
+            let mut list = Vec::new();
+            for item in items {
+                // an empty literal is an empty pallet, which isn't allowed
+                // between commas
+                if item.trim().is_empty() {
+                    return Err(Error::new(SyntaxError, loc, "Empty item in list literal"));
+                }
+                list.push(parse_assign_literal(&item.trim().to_owned(), loc)?);
+            }
+            return Ok(Pallet::List(list));
+        }
+        // map literal
+        let mut entries = Vec::new();
+        for item in items {
+            let parts = split_items(&item, ':', loc)?;
+            if parts.len() != 2 {
+                return Err(Error::new(
+                    SyntaxError,
+                    loc,
+                    "Malformed map literal, expected key: value entries",
+                ));
+            }
+            let key = parse_assign_literal(&parts[0].trim().to_owned(), loc)?;
+            let value = parse_assign_literal(&parts[1].trim().to_owned(), loc)?;
+            let key_literal = key.literal();
+            if map::insert(&mut entries, key, value).map_err(|e| Error::new(SyntaxError, loc, e))? {
+                return Err(Error::new(
+                    SyntaxError,
+                    loc,
+                    format!("Duplicate key {key_literal} in map literal"),
+                ));
+            }
+        }
+        return Ok(Pallet::Map(entries));
+    } else if s.starts_with('"') {
         // string literal
         if !s.ends_with('"') {
             return Err(Error::new(SyntaxError, loc, "Unclosed string literal"));
@@ -80,8 +127,13 @@ pub fn parse_assign_literal(s: &String, loc: SourceSpan) -> Result<Pallet, Error
 
     if !decimal && !float_terminal {
         // integer literal
-        match i64::from_str_radix(parsed_string.as_str(), 10) {
+        match parsed_string.parse::<i64>() {
             Ok(num) => return Ok(Pallet::Int(num)),
+            // literals too big for an int become big integers
+            #[cfg(feature = "bigint")]
+            Err(e) if *e.kind() == std::num::IntErrorKind::PosOverflow => {
+                return Ok(Pallet::BigInt(parsed_string.parse().unwrap()));
+            }
             Err(e) => {
                 return Err(Error::new(
                     SyntaxError,
@@ -104,3 +156,43 @@ pub fn parse_assign_literal(s: &String, loc: SourceSpan) -> Result<Pallet, Error
         };
     }
 }
+
+/// Splits the contents of a list or map literal on a separator, skipping
+/// separators inside string and character literals and nested collections
+fn split_items(s: &str, separator: char, loc: SourceSpan) -> Result<Vec<String>, Error> {
+    let mut items = Vec::new();
+    let mut item = String::new();
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    for c in s.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '[') => depth += 1,
+            (None, ']') if depth == 0 => {
+                return Err(Error::new(
+                    SyntaxError,
+                    loc,
+                    "Unexpected ']' in list literal",
+                ));
+            }
+            (None, ']') => depth -= 1,
+            (None, c) if c == separator && depth == 0 => {
+                items.push(std::mem::take(&mut item));
+                continue;
+            }
+            _ => {}
+        }
+        item.push(c);
+    }
+    if quote.is_some() || depth != 0 {
+        return Err(Error::new(
+            SyntaxError,
+            loc,
+            "Unclosed literal in list literal",
+        ));
+    }
+    items.push(item);
+    return Ok(items);
+}
@@ -1,6 +1,4 @@
-use std::collections::HashMap;
-
-mod literal_parser;
+pub(crate) mod literal_parser;
 
 use crate::*;
 use core::*;
@@ -32,13 +30,13 @@ fn get_next_char(pos: &mut SourcePos, char_map: &Vec<Vec<char>>) -> Option<char>
 }
 
 /// Identifies stations using a finite state machine. Returns a vector of stations
-/// discovered, and the assign table
+/// discovered
 pub fn parse_stations(
     char_map: &Vec<Vec<char>>,
     ns: &Namespace,
-) -> Result<(Vec<Station>, HashMap<usize, Pallet>), Error> {
+    config: &Config,
+) -> Result<Vec<Station>, Error> {
     let mut stations: Vec<Station> = Vec::new();
-    let mut assign_table: HashMap<usize, Pallet> = HashMap::new();
 
     let mut pos = SourcePos::zero();
     // getting first character
@@ -50,7 +48,7 @@ pub fn parse_stations(
                 "Empty factory file",
             ));
         }
-        if char_map[pos.line].len() > 0 {
+        if !char_map[pos.line].is_empty() {
             break char_map[pos.line][0];
         }
         pos.line += 1;
@@ -71,15 +69,15 @@ pub fn parse_stations(
                 '[' => {
                     state = State::Station;
                     cur_token = String::new();
-                    cur_station_pos = pos.clone();
-                    debug!(4, "   - station start @ {}", pos);
+                    cur_station_pos = pos;
+                    debug!(config, 4, "   - station start @ {}", pos);
                 }
                 // start of assign station
                 '{' => {
                     state = State::AssignStation;
                     cur_token = String::new();
-                    cur_station_pos = pos.clone();
-                    debug!(4, "   - assign station start @ {}", pos);
+                    cur_station_pos = pos;
+                    debug!(config, 4, "   - assign station start @ {}", pos);
                 }
                 // ehhh???
                 ']' | '}' => {
@@ -91,7 +89,7 @@ pub fn parse_stations(
             State::Station => {
                 if c == ']' {
                     // new station w no modifiers
-                    debug!(4, "   - station end @ {}", pos);
+                    debug!(config, 4, "   - station end @ {}", pos);
                     let new_station = Station::new(
                         cur_token.as_str(),
                         SourceSpan::new(cur_station_pos, cur_token.len() + 2),
@@ -99,10 +97,11 @@ pub fn parse_stations(
                         ns,
                     )?;
                     debug!(
+                        config,
                         3,
                         " - #{} {} @ {}",
                         stations.len(),
-                        new_station.logic.id,
+                        new_station.logic.id(),
                         new_station.loc
                     );
                     stations.push(new_station);
@@ -110,7 +109,7 @@ pub fn parse_stations(
                 } else if c == ':' {
                     // start of modifiers
                     state = State::StationModifiers(StationModifiers::default());
-                    debug!(4, "   - station modifiers @ {}", pos);
+                    debug!(config, 4, "   - station modifiers @ {}", pos);
                 } else if c.is_ascii_graphic() && !c.is_ascii_whitespace() {
                     // station identifier
                     cur_token.push(c);
@@ -130,18 +129,19 @@ pub fn parse_stations(
                 'W' => state = State::StationModifiers(mods.with_priority(Direction::WEST)),
                 '*' => state = State::StationModifiers(mods.reverse()),
                 ']' => {
-                    debug!(4, "   - station end @ {}", pos);
+                    debug!(config, 4, "   - station end @ {}", pos);
                     let new_station = Station::new(
                         cur_token.as_str(),
-                        SourceSpan::new(cur_station_pos, pos.col - cur_station_pos.col),
+                        SourceSpan::new(cur_station_pos, pos.col - cur_station_pos.col + 1),
                         *mods,
                         ns,
                     )?;
                     debug!(
+                        config,
                         3,
                         " - #{} {} @ {}",
                         stations.len(),
-                        new_station.logic.id,
+                        new_station.logic.id(),
                         new_station.loc
                     );
                     stations.push(new_station);
@@ -158,9 +158,9 @@ pub fn parse_stations(
             },
             State::AssignStation => match c {
                 '}' => {
-                    debug!(4, "   - station end @ {}", pos);
+                    debug!(config, 4, "   - station end @ {}", pos);
                     // creating new station
-                    let new_station = Station::new(
+                    let mut new_station = Station::new(
                         "assign",
                         SourceSpan::new(cur_station_pos, pos.col - cur_station_pos.col + 1),
                         StationModifiers::default(),
@@ -169,14 +169,15 @@ pub fn parse_stations(
                     // parsing literal type
                     let assignment_value = parse_assign_literal(&cur_token, new_station.loc)?;
                     debug!(
+                        config,
                         3,
                         " - #{} {} @ {} ({})",
                         stations.len(),
-                        new_station.logic.id,
+                        new_station.logic.id(),
                         new_station.loc,
                         assignment_value
                     );
-                    assign_table.insert(stations.len(), assignment_value);
+                    new_station.config = Some(assignment_value);
                     stations.push(new_station);
                     state = State::Default;
                 }
@@ -206,14 +207,14 @@ pub fn parse_stations(
         c = match get_next_char(&mut pos, char_map) {
             Some(c) => c,
             None => {
-                debug!(4, "   - EOF reached");
+                debug!(config, 4, "   - EOF reached");
                 break;
             }
         };
     }
     match state {
         State::Default => {
-            return Ok((stations, assign_table));
+            return Ok(stations);
         }
         _ => return Err(Error::new(SyntaxError, cur_station_pos, "Unexpected EOF")),
     }
@@ -25,45 +25,57 @@ fn test_get_next_char() {
 #[test]
 fn test_parse_stations() {
     let char_map = make_char_map("[start] [print]\n  [exit]");
-    let (stations, _) = parse_stations(&char_map, &STATION_TYPES).unwrap();
-    assert_eq!(stations[0].logic.id, "start");
+    let stations = parse_stations(&char_map, &STATION_TYPES, &Config::default()).unwrap();
+    assert_eq!(stations[0].logic.id(), "start");
     assert_eq!(stations[0].loc, SourceSpan::new(SourcePos::zero(), 7));
-    assert_eq!(stations[1].logic.id, "print");
+    assert_eq!(stations[1].logic.id(), "print");
     assert_eq!(stations[1].loc, SourceSpan::new(SourcePos::new(0, 8), 7));
-    assert_eq!(stations[2].logic.id, "exit");
+    assert_eq!(stations[2].logic.id(), "exit");
     assert_eq!(stations[2].loc, SourceSpan::new(SourcePos::new(1, 2), 6));
 }
 
 #[test]
 fn test_parse_stations_incomplete() {
     let char_map = make_char_map("[start] [print]\n  [ex");
-    assert!(parse_stations(&char_map, &STATION_TYPES).is_err());
+    assert!(parse_stations(&char_map, &STATION_TYPES, &Config::default()).is_err());
 }
 
 #[test]
 fn test_parse_stations_assign() {
     let char_map = make_char_map("[start] {} [exit]");
-    let (stations, _) = parse_stations(&char_map, &STATION_TYPES).unwrap();
-    assert_eq!(stations[0].logic.id, "start");
+    let stations = parse_stations(&char_map, &STATION_TYPES, &Config::default()).unwrap();
+    assert_eq!(stations[0].logic.id(), "start");
     assert_eq!(stations[0].loc, SourceSpan::new(SourcePos::zero(), 7));
-    assert_eq!(stations[1].logic.id, "assign");
+    assert_eq!(stations[1].logic.id(), "assign");
     assert_eq!(stations[1].loc, SourceSpan::new(SourcePos::new(0, 8), 2));
-    assert_eq!(stations[2].logic.id, "exit");
+    assert_eq!(stations[1].config, Some(Pallet::Empty));
+    assert_eq!(stations[2].logic.id(), "exit");
     assert_eq!(stations[2].loc, SourceSpan::new(SourcePos::new(0, 11), 6));
 }
 
 #[test]
 fn test_parse_stations_joint() {
     let char_map = make_char_map("[start] [] [exit]");
-    let (stations, _) = parse_stations(&char_map, &STATION_TYPES).unwrap();
-    assert_eq!(stations[0].logic.id, "start");
+    let stations = parse_stations(&char_map, &STATION_TYPES, &Config::default()).unwrap();
+    assert_eq!(stations[0].logic.id(), "start");
     assert_eq!(stations[0].loc, SourceSpan::new(SourcePos::zero(), 7));
-    assert_eq!(stations[1].logic.id, "joint");
+    assert_eq!(stations[1].logic.id(), "joint");
     assert_eq!(stations[1].loc, SourceSpan::new(SourcePos::new(0, 8), 2));
-    assert_eq!(stations[2].logic.id, "exit");
+    assert_eq!(stations[2].logic.id(), "exit");
     assert_eq!(stations[2].loc, SourceSpan::new(SourcePos::new(0, 11), 6));
 }
 
+#[test]
+fn test_parse_stations_modifiers() {
+    let char_map = make_char_map("[start] [latch:W*] [exit]");
+    let stations = parse_stations(&char_map, &STATION_TYPES, &Config::default()).unwrap();
+    assert_eq!(stations[1].logic.id(), "latch");
+    assert_eq!(stations[1].loc, SourceSpan::new(SourcePos::new(0, 8), 10));
+    assert_eq!(stations[1].modifiers.priority, Direction::WEST);
+    assert!(stations[1].modifiers.reverse);
+    assert_eq!(stations[2].loc, SourceSpan::new(SourcePos::new(0, 19), 6));
+}
+
 #[test]
 fn test_parse_assign_literal_empty() {
     assert_eq!(
@@ -137,6 +149,29 @@ fn test_parse_assign_literal_integer() {
     assert!(parse_assign_literal(&"123_4a56".to_owned(), SourceSpan::zero()).is_err());
 }
 
+#[test]
+#[cfg(not(feature = "bigint"))]
+fn test_parse_assign_literal_integer_overflow() {
+    assert!(parse_assign_literal(&"9223372036854775808".to_owned(), SourceSpan::zero()).is_err());
+}
+
+#[test]
+#[cfg(feature = "bigint")]
+fn test_parse_assign_literal_bigint() {
+    assert_eq!(
+        parse_assign_literal(&"9223372036854775807".to_owned(), SourceSpan::zero())
+            .ok()
+            .unwrap(),
+        Pallet::Int(i64::MAX)
+    );
+    assert_eq!(
+        parse_assign_literal(&"9_223_372_036_854_775_808".to_owned(), SourceSpan::zero())
+            .ok()
+            .unwrap(),
+        Pallet::BigInt("9223372036854775808".parse().unwrap())
+    );
+}
+
 #[test]
 fn test_parse_assign_literal_float() {
     assert_eq!(
@@ -161,3 +196,65 @@ fn test_parse_assign_literal_float() {
     assert!(parse_assign_literal(&"1.23.4".to_owned(), SourceSpan::zero()).is_err());
     assert!(parse_assign_literal(&"12a3.0".to_owned(), SourceSpan::zero()).is_err());
 }
+
+#[test]
+fn test_parse_assign_literal_list() {
+    assert_eq!(
+        parse_assign_literal(&"[1, 2, \"a, b\"]".to_owned(), SourceSpan::zero())
+            .ok()
+            .unwrap(),
+        Pallet::List(vec![
+            Pallet::Int(1),
+            Pallet::Int(2),
+            Pallet::String("a, b".to_owned())
+        ])
+    );
+    assert_eq!(
+        parse_assign_literal(&"[[], [','], true]".to_owned(), SourceSpan::zero())
+            .ok()
+            .unwrap(),
+        Pallet::List(vec![
+            Pallet::List(Vec::new()),
+            Pallet::List(vec![Pallet::Char(',')]),
+            Pallet::Bool(true)
+        ])
+    );
+    assert!(parse_assign_literal(&"[1, 2".to_owned(), SourceSpan::zero()).is_err());
+    assert!(parse_assign_literal(&"[1, [2]".to_owned(), SourceSpan::zero()).is_err());
+    assert!(parse_assign_literal(&"[\"a]".to_owned(), SourceSpan::zero()).is_err());
+}
+
+#[test]
+fn test_parse_assign_literal_list_empty_item() {
+    for s in ["[1, 2,]", "[,]", "[1, , 2]", "[,1]"] {
+        let err = parse_assign_literal(&s.to_owned(), SourceSpan::zero()).unwrap_err();
+        assert_eq!(err.msg, "Empty item in list literal");
+    }
+}
+
+#[test]
+fn test_parse_assign_literal_map() {
+    assert_eq!(
+        parse_assign_literal(
+            &"[\"b\": 2, 'a': [1], 3: \"x: y\"]".to_owned(),
+            SourceSpan::zero()
+        )
+        .ok()
+        .unwrap(),
+        Pallet::Map(vec![
+            (Pallet::Int(3), Pallet::String("x: y".to_owned())),
+            (Pallet::Char('a'), Pallet::List(vec![Pallet::Int(1)])),
+            (Pallet::String("b".to_owned()), Pallet::Int(2)),
+        ])
+    );
+    assert_eq!(
+        parse_assign_literal(&"[:]".to_owned(), SourceSpan::zero())
+            .ok()
+            .unwrap(),
+        Pallet::Map(Vec::new())
+    );
+    assert!(parse_assign_literal(&"[1: 2, 1: 3]".to_owned(), SourceSpan::zero()).is_err());
+    assert!(parse_assign_literal(&"[1: 2, 3]".to_owned(), SourceSpan::zero()).is_err());
+    assert!(parse_assign_literal(&"[[1]: 2]".to_owned(), SourceSpan::zero()).is_err());
+    assert!(parse_assign_literal(&"[\"a\": 1,]".to_owned(), SourceSpan::zero()).is_err());
+}
@@ -5,7 +5,7 @@
 //     let lines: Vec<&str> = vec!["[start]═─{\"hello world\"}═─[println]"];
 //     let (stations, start_i, assign_table) = process(&lines, &builtins::MANIFEST).unwrap();
 //     assert_eq!(stations.len(), 3);
-//     assert_eq!(stations[start_i].logic.id, "start");
+//     assert_eq!(stations[start_i].logic.id(), "start");
 //     assert_eq!(assign_table.len(), 1);
 // }
 
@@ -0,0 +1,238 @@
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crate::*;
+
+/// Heat map background colors, from coldest to hottest (ANSI 256 color codes)
+const HEAT_COLORS: [u8; 5] = [22, 58, 94, 130, 160];
+
+/// Counters of a single station collected by a profiler
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StationProfile {
+    /// Number of times the station's procedure was triggered
+    pub triggers: usize,
+    /// Pallets delivered to the station's input bays
+    pub pallets_in: usize,
+    /// Pallets sent out of the station's output bays
+    pub pallets_out: usize,
+    /// Triggers that produced no pallet
+    pub drops: usize,
+    /// Time spent in the station's procedure
+    pub time: Duration,
+}
+
+/// Column a profile table is sorted by, in descending order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortKey {
+    Index,
+    Triggers,
+    In,
+    Out,
+    Drops,
+    Time,
+}
+impl FromStr for SortKey {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return match s {
+            "index" => Ok(SortKey::Index),
+            "triggers" => Ok(SortKey::Triggers),
+            "in" => Ok(SortKey::In),
+            "out" => Ok(SortKey::Out),
+            "drops" => Ok(SortKey::Drops),
+            "time" => Ok(SortKey::Time),
+            _ => Err(format!(
+                "Unknown sort key \"{s}\", expected one of index, triggers, in, out, drops, time"
+            )),
+        };
+    }
+}
+
+/// Observer that counts what every station did, and how long its procedure took
+#[derive(Debug, Clone, Default)]
+pub struct Profiler {
+    /// Profiles of each station, by index
+    pub stations: Vec<StationProfile>,
+    /// Station whose procedure is running, and when it was triggered
+    running: Option<(usize, Instant)>,
+}
+impl Profiler {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    /// Adds the time since the running station was triggered to its profile
+    fn stop_timer(&mut self) {
+        if let Some((i, start)) = self.running.take() {
+            self.stations[i].time += start.elapsed();
+        }
+    }
+
+    /// Renders a table of every station's counters, sorted by a column
+    pub fn table(&self, stations: &[Station], sort: SortKey) -> String {
+        let mut order: Vec<usize> = (0..self.stations.len()).collect();
+        order.sort_by(|a, b| {
+            let (a, b) = (&self.stations[*a], &self.stations[*b]);
+            return match sort {
+                SortKey::Index => std::cmp::Ordering::Equal,
+                SortKey::Triggers => b.triggers.cmp(&a.triggers),
+                SortKey::In => b.pallets_in.cmp(&a.pallets_in),
+                SortKey::Out => b.pallets_out.cmp(&a.pallets_out),
+                SortKey::Drops => b.drops.cmp(&a.drops),
+                SortKey::Time => b.time.cmp(&a.time),
+            };
+        });
+
+        let mut table = format!(
+            "{:>5}  {:<12} {:<10} {:>9} {:>9} {:>9} {:>9} {:>11}\n",
+            "#", "station", "location", "triggers", "in", "out", "drops", "time (ms)"
+        );
+        for i in order {
+            let profile = &self.stations[i];
+            table += &format!(
+                "{:>5}  {:<12} {:<10} {:>9} {:>9} {:>9} {:>9} {:>11.3}\n",
+                format!("#{i}"),
+                stations[i].logic.id(),
+                stations[i].loc.pos.to_string(),
+                profile.triggers,
+                profile.pallets_in,
+                profile.pallets_out,
+                profile.drops,
+                profile.time.as_secs_f64() * 1000.0
+            );
+        }
+        return table;
+    }
+
+    /// Renders the source code with every line annotated with the number of
+    /// times its stations were triggered. If colored, stations are also
+    /// highlighted as a heat map, hotter stations having been triggered more
+    pub fn annotated_source(&self, src: &str, stations: &[Station], color: bool) -> String {
+        let lines: Vec<Vec<char>> = src.split('\n').map(|l| l.chars().collect()).collect();
+        let width = lines.iter().map(|l| l.len()).max().unwrap_or(0);
+        let max_triggers = self.stations.iter().map(|p| p.triggers).max().unwrap_or(0);
+
+        let mut output = String::new();
+        for (line_i, line) in lines.iter().enumerate() {
+            // stations on this line, in order of column
+            let mut on_line: Vec<usize> = (0..stations.len())
+                .filter(|i| stations[*i].loc.pos.line == line_i)
+                .collect();
+            on_line.sort_by_key(|i| stations[*i].loc.pos.col);
+
+            let mut col = 0;
+            for i in on_line.iter() {
+                let loc = stations[*i].loc;
+                output.extend(&line[col..loc.pos.col]);
+                let station_src: String =
+                    line[loc.pos.col..(loc.pos.col + loc.len)].iter().collect();
+                let triggers = self.stations[*i].triggers;
+                if color && triggers > 0 {
+                    let heat = (triggers * HEAT_COLORS.len() - 1) / max_triggers;
+                    output += &format!("\x1b[1;97;48;5;{}m{station_src}\x1b[0m", HEAT_COLORS[heat]);
+                } else {
+                    output += &station_src;
+                }
+                col = loc.pos.col + loc.len;
+            }
+            output.extend(&line[col..]);
+
+            if !on_line.is_empty() {
+                output += &" ".repeat(width - line.len());
+                output += "  │ ";
+                output += &on_line
+                    .iter()
+                    .map(|i| {
+                        format!(
+                            "#{i} [{}] {}",
+                            stations[*i].logic.id(),
+                            self.stations[*i].triggers
+                        )
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ");
+            }
+            output.push('\n');
+        }
+        return output;
+    }
+}
+impl Observer for Profiler {
+    fn on_event(&mut self, event: &Event, stations: &[Station]) {
+        if self.stations.len() != stations.len() {
+            self.stations = vec![StationProfile::default(); stations.len()];
+        }
+        match *event {
+            Event::PalletMoved { from, to, .. } => {
+                self.stations[from].pallets_out += 1;
+                self.stations[to].pallets_in += 1;
+            }
+            Event::StationTriggered { station } => {
+                self.stop_timer();
+                self.stations[station].triggers += 1;
+                self.running = Some((station, Instant::now()));
+            }
+            Event::PalletDropped { station } => {
+                self.stop_timer();
+                self.stations[station].drops += 1;
+            }
+            Event::PalletProduced { .. } | Event::Halted { .. } | Event::StepEnd { .. } => {
+                self.stop_timer();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: &str = "\
+[start]═─{3}═─[count]═─[println]
+   ╚─────────────┘";
+
+    fn profile() -> (Program, Profiler) {
+        let program = Program::new(SRC).unwrap();
+        let mut profiler = Profiler::new();
+        Interpreter::new()
+            .stdout(std::io::sink())
+            .run_observed(&program, &mut profiler)
+            .unwrap();
+        return (program, profiler);
+    }
+
+    #[test]
+    fn test_profile_counts() {
+        let (_, profiler) = profile();
+        // start, assign, count, println
+        assert_eq!(profiler.stations[0].pallets_out, 2);
+        assert_eq!(profiler.stations[1].triggers, 1);
+        assert_eq!(profiler.stations[2].pallets_in, 2);
+        assert_eq!(profiler.stations[2].triggers, 2);
+        assert_eq!(profiler.stations[2].drops, 0);
+        assert_eq!(profiler.stations[3].triggers, 2);
+        assert_eq!(profiler.stations[3].drops, 2);
+    }
+
+    #[test]
+    fn test_profile_table() {
+        let (program, profiler) = profile();
+        let table = profiler.table(program.stations(), SortKey::Drops);
+        let rows: Vec<&str> = table.lines().collect();
+        assert_eq!(rows.len(), 5);
+        assert!(rows[1].starts_with("   #3  println      1:23"));
+    }
+
+    #[test]
+    fn test_annotated_source() {
+        let (program, profiler) = profile();
+        let annotated = profiler.annotated_source(SRC, program.stations(), false);
+        assert_eq!(
+            annotated,
+            "[start]═─{3}═─[count]═─[println]  │ #0 [start] 0, #1 [assign] 1, #2 [count] 2, #3 [println] 2\n   ╚─────────────┘\n"
+        );
+    }
+}
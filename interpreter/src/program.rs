@@ -0,0 +1,354 @@
+use std::io::{stderr, stdin, stdout, BufRead, BufReader, Read, Write};
+use std::time::{Duration, Instant};
+
+use crate::*;
+
+/// A preprocessed FactoryScript program, ready to be run any number of times
+#[derive(Debug, Clone)]
+pub struct Program {
+    /// Stations of the program, in their initial state
+    stations: Vec<Station>,
+    /// Time spent preprocessing the source code
+    preprocess_time: Duration,
+}
+impl Program {
+    /// Preprocesses FactoryScript source code into a program, using the builtin
+    /// station types
+    pub fn new(src: &str) -> Result<Self, Error> {
+        return Self::with_namespace(src, &STATION_TYPES);
+    }
+
+    /// Preprocesses FactoryScript source code into a program, using the station
+    /// types of a namespace
+    pub fn with_namespace(src: &str, ns: &Namespace) -> Result<Self, Error> {
+        return Self::with_config(src, ns, &Config::default());
+    }
+
+    /// Preprocesses FactoryScript source code into a program, using the station
+    /// types of a namespace and a configuration
+    pub fn with_config(src: &str, ns: &Namespace, config: &Config) -> Result<Self, Error> {
+        let start_time = Instant::now();
+        debug!(config, 2, "Preprocessing...");
+        let stations = preprocessor::process(src, ns, config)?;
+        return Ok(Self {
+            stations,
+            preprocess_time: start_time.elapsed(),
+        });
+    }
+
+    /// The program's stations
+    pub fn stations(&self) -> &Vec<Station> {
+        return &self.stations;
+    }
+
+    /// Lowers the program to IR, for running with a configuration. Unless
+    /// disabled by the configuration, the program is optimized first
+    pub fn compile(&self, config: &Config) -> Ir {
+        if config.optimize {
+            let stations = optimizer::optimize(self.stations.clone(), config);
+            return Ir::compile(&stations, config);
+        }
+        return Ir::compile(&self.stations, config);
+    }
+}
+
+/// Runs programs, with the input and output streams supplied by the host
+pub struct Interpreter<'a> {
+    config: Config,
+    stdin: Box<dyn BufRead + 'a>,
+    stdout: Box<dyn Write + 'a>,
+    stderr: Box<dyn Write + 'a>,
+}
+impl Default for Interpreter<'_> {
+    /// Interpreter using the process's standard streams
+    fn default() -> Self {
+        Self {
+            config: Config::default(),
+            stdin: Box::new(BufReader::new(stdin())),
+            stdout: Box::new(stdout()),
+            stderr: Box::new(stderr()),
+        }
+    }
+}
+impl<'a> Interpreter<'a> {
+    /// Creates an interpreter using the process's standard streams
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    /// Sets the configuration used for preprocessing and running programs
+    pub fn config(self, config: Config) -> Self {
+        return Self { config, ..self };
+    }
+
+    /// Sets the stream programs read their input from
+    pub fn stdin<R: Read + 'a>(self, stdin: R) -> Self {
+        return Self {
+            stdin: Box::new(BufReader::new(stdin)),
+            ..self
+        };
+    }
+
+    /// Records every line programs read from the input stream into a session
+    pub fn record_stdin(self, session: &Session) -> Self {
+        return Self {
+            stdin: Box::new(session.record(self.stdin)),
+            ..self
+        };
+    }
+
+    /// Sets the stream programs write their output to
+    pub fn stdout<W: Write + 'a>(self, stdout: W) -> Self {
+        return Self {
+            stdout: Box::new(stdout),
+            ..self
+        };
+    }
+
+    /// Sets the stream programs write their errors to
+    pub fn stderr<W: Write + 'a>(self, stderr: W) -> Self {
+        return Self {
+            stderr: Box::new(stderr),
+            ..self
+        };
+    }
+
+    /// Runs a program to completion. Unless pure stations run in parallel,
+    /// the program is compiled to IR first, which runs faster
+    pub fn run(&mut self, program: &Program) -> Result<Report, Error> {
+        if self.config.parallel {
+            return self.run_observed(program, &mut ());
+        }
+        let mut ctx = self.context();
+
+        let runtime_start_time = Instant::now();
+        debug!(ctx.config, 2, "Compiling...");
+        let ir = program.compile(ctx.config);
+        debug!(ctx.config, 2, "Starting");
+        let steps = ir.execute(&mut ctx)?;
+        let runtime_time = runtime_start_time.elapsed();
+        let _ = self.stdout.flush();
+
+        return Ok(Report {
+            steps,
+            preprocess_time: program.preprocess_time,
+            runtime_time,
+        });
+    }
+
+    /// Runs a program to completion, reporting every event to an observer
+    pub fn run_observed(
+        &mut self,
+        program: &Program,
+        observer: &mut dyn Observer,
+    ) -> Result<Report, Error> {
+        let mut ctx = self.context();
+
+        let runtime_start_time = Instant::now();
+        debug!(ctx.config, 2, "Starting");
+        let steps = runtime::execute(program.stations.clone(), &mut ctx, observer)?;
+        let runtime_time = runtime_start_time.elapsed();
+        let _ = self.stdout.flush();
+
+        return Ok(Report {
+            steps,
+            preprocess_time: program.preprocess_time,
+            runtime_time,
+        });
+    }
+
+    /// Runs a program, or continues a runtime that was stopped, reporting every
+    /// event to an observer. After each step the runtime is passed to `stop`,
+    /// and if it returns true (or the observer asks to stop) the run stops
+    /// there and the runtime is returned so it can be continued or saved as a
+    /// snapshot
+    pub fn run_until(
+        &mut self,
+        program: &Program,
+        runtime: Option<Runtime>,
+        observer: &mut dyn Observer,
+        stop: &mut dyn FnMut(&Runtime) -> bool,
+    ) -> Result<(Report, Option<Runtime>), Error> {
+        let mut ctx = self.context();
+
+        let runtime_start_time = Instant::now();
+        debug!(ctx.config, 2, "Starting");
+        let mut runtime = match runtime {
+            Some(runtime) => {
+                runtime.resume_clock(&mut ctx);
+                runtime
+            }
+            None => Runtime::new(program.stations.clone(), &mut ctx, observer),
+        };
+        let mut stopped = false;
+        while !runtime.is_finished() {
+            runtime.step(&mut ctx, observer)?;
+            if !runtime.is_finished() && (observer.stop_requested() || stop(&runtime)) {
+                debug!(ctx.config, 2, "Stopped after step {}", runtime.step_count);
+                runtime.elapsed = ctx.elapsed();
+                stopped = true;
+                break;
+            }
+        }
+        let runtime_time = runtime_start_time.elapsed();
+        let _ = self.stdout.flush();
+
+        let report = Report {
+            steps: runtime.step_count,
+            preprocess_time: program.preprocess_time,
+            runtime_time,
+        };
+        return Ok((report, if stopped { Some(runtime) } else { None }));
+    }
+
+    /// Context for running station procedures with this interpreter's
+    /// configuration and streams
+    pub fn context(&mut self) -> Context<'_> {
+        return Context {
+            config: &self.config,
+            stdin: &mut self.stdin,
+            stdout: &mut self.stdout,
+            stderr: &mut self.stderr,
+            start: self.config.clock.elapsed(),
+            offset: Duration::ZERO,
+        };
+    }
+
+    /// Preprocesses and runs FactoryScript source code
+    pub fn run_src(&mut self, src: &str) -> Result<Report, Error> {
+        let program = Program::with_config(src, &STATION_TYPES, &self.config)?;
+        return self.run(&program);
+    }
+}
+
+/// Summary of a completed program run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Report {
+    /// Number of steps executed
+    pub steps: usize,
+    /// Time spent preprocessing the source code
+    pub preprocess_time: Duration,
+    /// Time spent executing
+    pub runtime_time: Duration,
+}
+impl Report {
+    /// Total time spent preprocessing and executing
+    pub fn total_time(&self) -> Duration {
+        return self.preprocess_time + self.runtime_time;
+    }
+
+    /// Average time spent executing each step
+    pub fn avg_step_time(&self) -> Duration {
+        return self
+            .runtime_time
+            .checked_div(self.steps as u32)
+            .unwrap_or(Duration::ZERO);
+    }
+}
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "======Benchmark======")?;
+        writeln!(f, " steps      {}", self.steps)?;
+        writeln!(
+            f,
+            " avg step   {:.2}ms",
+            self.avg_step_time().as_secs_f64() * 1000.0
+        )?;
+        writeln!(f)?;
+        writeln!(f, " preprocess {:.5}s", self.preprocess_time.as_secs_f64())?;
+        writeln!(f, " runtime    {:.5}s", self.runtime_time.as_secs_f64())?;
+        writeln!(f, " total      {:.5}s", self.total_time().as_secs_f64())?;
+        write!(f, "=====================")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_captured_output() {
+        let program = Program::new("[start]═─{\"hello world\"}═─[println]").unwrap();
+        let mut output: Vec<u8> = Vec::new();
+        let report = Interpreter::new()
+            .stdout(&mut output)
+            .run(&program)
+            .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "hello world\n");
+        assert_eq!(report.steps, 2);
+    }
+
+    #[test]
+    fn test_physical_belts() {
+        let program = Program::new("[start]═──────{\"hi\"}═─[println]").unwrap();
+        for (physical_belts, steps) in [(false, 2), (true, 9)] {
+            let mut output: Vec<u8> = Vec::new();
+            let report = Interpreter::new()
+                .config(Config::default().with_physical_belts(physical_belts))
+                .stdout(&mut output)
+                .run(&program)
+                .unwrap();
+            assert_eq!(String::from_utf8(output).unwrap(), "hi\n");
+            assert_eq!(report.steps, steps);
+        }
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        for src in [
+            include_str!("../../examples/fizzbuzz.factory"),
+            include_str!("../../examples/fibonacci.factory"),
+        ] {
+            let program = Program::new(src).unwrap();
+            let mut runs = Vec::new();
+            for parallel in [false, true] {
+                let mut output: Vec<u8> = Vec::new();
+                let report = Interpreter::new()
+                    .config(Config::default().with_parallel(parallel))
+                    .stdout(&mut output)
+                    .run(&program)
+                    .unwrap();
+                runs.push((output, report.steps));
+            }
+            assert!(!runs[0].0.is_empty());
+            assert_eq!(runs[0], runs[1]);
+        }
+    }
+
+    #[test]
+    fn test_supplied_input() {
+        let mut output: Vec<u8> = Vec::new();
+        Interpreter::new()
+            .stdin("Jaxson\n".as_bytes())
+            .stdout(&mut output)
+            .run_src("[start]═─[readln]═─[println]")
+            .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "Jaxson\n");
+    }
+
+    #[test]
+    fn test_concurrent_runs() {
+        let program = Program::new("[start]═─[readln]═─[println]").unwrap();
+        std::thread::scope(|s| {
+            let handles: Vec<_> = (0..8)
+                .map(|i| {
+                    let program = &program;
+                    s.spawn(move || {
+                        let input = format!("{i}\n");
+                        let mut output: Vec<u8> = Vec::new();
+                        Interpreter::new()
+                            .stdin(input.as_bytes())
+                            .stdout(&mut output)
+                            .run(program)
+                            .unwrap();
+                        (input, output)
+                    })
+                })
+                .collect();
+            for handle in handles {
+                let (input, output) = handle.join().unwrap();
+                assert_eq!(String::from_utf8(output).unwrap(), input);
+            }
+        });
+    }
+}
@@ -1,131 +1,446 @@
-use fs_core::Pallet;
-use std::collections::HashMap;
+use std::{
+    io, thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
 
 use crate::*;
 
-/// Spawns pallets from the start station and starts the execution loop, returns
-/// the number of steps in the program
+/// Spawns pallets from the stations that produce them at startup (the start
+/// station) and runs the program to completion, returns the number of steps in
+/// the program
 pub fn execute(
-    stations: &mut Vec<Station>,
-    start_i: usize,
-    assign_table: &HashMap<usize, Pallet>,
+    stations: Vec<Station>,
+    ctx: &mut Context,
+    observer: &mut dyn Observer,
 ) -> Result<usize, Error> {
-    // Vector of all pallets to move in the next step, tuple with the pallet and
-    // the destination index and bay number
-    let mut moving_pallets: Vec<(Pallet, (usize, usize))> = Vec::new();
-
-    // begin from start station
-    let start_station = &stations[start_i];
-    for out_bay in start_station.out_bays.iter() {
-        moving_pallets.push((Pallet::Empty, *out_bay));
+    let mut runtime = Runtime::new(stations, ctx, observer);
+    while !runtime.is_finished() {
+        runtime.step(ctx, observer)?;
+        if observer.stop_requested() {
+            debug!(ctx.config, 2, "Stopped by an observer");
+            return Ok(runtime.step_count);
+        }
     }
-    debug!(3, "Start pallets spawned at #{start_i}");
+    debug!(ctx.config, 2, "No remaining moving pallets");
+    return Ok(runtime.step_count);
+}
 
-    let mut step_count: usize = 0;
-    'execution_loop: while !moving_pallets.is_empty() {
-        // recording start time of iteration
-        let step_start_t = Instant::now();
+/// State of a running program, which can be advanced a step at a time. Each
+/// step is split into two phases, delivering the moving pallets to their bays
+/// and then firing the triggered stations, so the program can be inspected in
+/// between
+#[derive(Debug, Clone)]
+pub struct Runtime {
+    pub stations: Vec<Station>,
+    /// All pallets travelling along conveyor belts
+    pub moving_pallets: Vec<MovingPallet>,
+    /// Number of completed steps
+    pub step_count: usize,
+    /// Time the program had been running for by the configured clock when it
+    /// was last stopped, which time stations carry on from when it continues
+    pub elapsed: Duration,
+    /// Whether the pallets of the current step have been delivered, but the
+    /// stations haven't fired yet
+    delivered: bool,
+    halted: bool,
+}
+impl Runtime {
+    /// Spawns pallets from the stations that produce them at startup (the start
+    /// station)
+    pub fn new(stations: Vec<Station>, ctx: &mut Context, observer: &mut dyn Observer) -> Self {
+        ctx.start = ctx.config.clock.elapsed();
+        ctx.offset = Duration::ZERO;
+        let mut moving_pallets = Vec::new();
+        for (i, station) in stations.iter().enumerate() {
+            if let Some(p) = station.logic.spawn() {
+                observer.on_event(
+                    &Event::PalletProduced {
+                        station: i,
+                        pallet: &p,
+                        side: None,
+                    },
+                    &stations,
+                );
+                for out_bay in station.out_bays.iter() {
+                    moving_pallets.push(MovingPallet::new(p.clone(), i, out_bay, ctx.config));
+                }
+                debug!(ctx.config, 3, "Start pallets spawned at #{i}");
+            }
+        }
+        return Self {
+            stations,
+            moving_pallets,
+            step_count: 0,
+            elapsed: Duration::ZERO,
+            delivered: false,
+            halted: false,
+        };
+    }
+
+    /// Continues a program from the state it was in between two steps, such
+    /// as one restored from a snapshot
+    pub fn resume(
+        stations: Vec<Station>,
+        moving_pallets: Vec<MovingPallet>,
+        step_count: usize,
+        elapsed: Duration,
+    ) -> Self {
+        return Self {
+            stations,
+            moving_pallets,
+            step_count,
+            elapsed,
+            delivered: false,
+            halted: false,
+        };
+    }
+
+    /// Starts the clock of a program continuing after it was stopped, so time
+    /// stations carry on from the time it had been running for
+    pub fn resume_clock(&self, ctx: &mut Context) {
+        ctx.start = ctx.config.clock.elapsed();
+        ctx.offset = self.elapsed;
+    }
+
+    /// Whether the program has halted or has no pallets left to move
+    pub fn is_finished(&self) -> bool {
+        return self.halted || (!self.delivered && self.moving_pallets.is_empty());
+    }
+
+    /// Whether the current step's pallets have been delivered and the stations
+    /// are waiting to fire
+    pub fn is_mid_step(&self) -> bool {
+        return self.delivered;
+    }
 
-        // moving the pallets
-        for (pallet, dest) in moving_pallets.iter() {
-            debug!(3, " - pallet moved to #{}:{} ({})", dest.0, dest.1, pallet);
-            stations[dest.0].in_bays[dest.1] = Some(pallet.clone());
+    /// Runs the rest of the current step
+    pub fn step(&mut self, ctx: &mut Context, observer: &mut dyn Observer) -> Result<(), Error> {
+        if !self.delivered {
+            self.deliver(ctx, observer);
         }
-        moving_pallets.clear();
-        // executing station procedures
+        return self.fire(ctx, observer);
+    }
+
+    /// Starts a step by moving every pallet into its destination bay, does
+    /// nothing if they have already been delivered
+    pub fn deliver(&mut self, ctx: &mut Context, observer: &mut dyn Observer) {
+        if self.delivered || self.is_finished() {
+            return;
+        }
+        observer.on_event(
+            &Event::StepStart {
+                step: self.step_count,
+            },
+            &self.stations,
+        );
+        let stations = &mut self.stations;
+        let (arriving, travelling): (Vec<MovingPallet>, Vec<MovingPallet>) = self
+            .moving_pallets
+            .drain(..)
+            .partition(|moving| moving.delay == 0);
+        self.moving_pallets = travelling;
+        for moving in self.moving_pallets.iter_mut() {
+            moving.delay -= 1;
+        }
+        for MovingPallet {
+            pallet,
+            origin,
+            dest,
+            ..
+        } in arriving
+        {
+            debug!(
+                ctx.config,
+                3, " - pallet moved to #{}:{} ({})", dest.0, dest.1, pallet
+            );
+            if let Some(old_pallet) = &stations[dest.0].in_bays[dest.1] {
+                observer.on_event(
+                    &Event::PalletOverwritten {
+                        station: dest.0,
+                        bay: dest.1,
+                        pallet: old_pallet,
+                    },
+                    stations,
+                );
+            }
+            observer.on_event(
+                &Event::PalletMoved {
+                    from: origin,
+                    to: dest.0,
+                    bay: dest.1,
+                    pallet: &pallet,
+                },
+                stations,
+            );
+            stations[dest.0].in_bays[dest.1] = Some(pallet);
+        }
+        self.delivered = true;
+    }
+
+    /// Indices of the stations that will fire when the current step continues
+    pub fn triggered(&self) -> Vec<usize> {
+        if !self.delivered {
+            return Vec::new();
+        }
+        return (0..self.stations.len())
+            .filter(|i| {
+                self.stations[*i]
+                    .logic
+                    .triggered(&self.stations[*i].in_bays)
+            })
+            .collect();
+    }
+
+    /// Finishes a step by executing the procedures of the triggered stations
+    fn fire(&mut self, ctx: &mut Context, observer: &mut dyn Observer) -> Result<(), Error> {
+        if !self.delivered {
+            return Ok(());
+        }
+        // recording start time of iteration
+        let step_start_t = Instant::now();
+        self.delivered = false;
+        let stations = &mut self.stations;
+        let mut results = if ctx.config.parallel {
+            run_pure_procedures(stations, ctx.config)
+        } else {
+            Vec::new()
+        };
         for i in 0..stations.len() {
-            let station = &mut stations[i];
-            // counting occupied bays
-            let mut occupied_bays = 0;
-            for bay in station.in_bays.iter() {
-                if bay.is_some() {
-                    occupied_bays += 1;
-                }
+            if !stations[i].logic.triggered(&stations[i].in_bays) {
+                continue;
             }
-            if occupied_bays >= station.logic.inputs && station.logic.inputs > 0 {
-                // running procedures
-                debug!(3, " - Procedure triggered on #{i} ({})", station.logic.id);
-                // handling special case stations
-                if station.logic.id == "assign" {
-                    // special case: assign station
-                    if let Some(p) = assign_table.get(&i) {
-                        debug!(4, "    - Produced: {}", p);
-                        for out_bay in station.out_bays.iter() {
-                            moving_pallets.push((p.clone(), *out_bay));
-                        }
-                    } else {
-                        return Err(Error::new(
-                            RuntimeError,
-                            station.loc,
-                            format!("Can't find assign table entry for #{i}"),
+            debug!(
+                ctx.config,
+                3,
+                " - Procedure triggered on #{i} ({})",
+                stations[i].logic.id()
+            );
+            observer.on_event(&Event::StationTriggered { station: i }, stations);
+            // pure procedures may have already run on another thread, their
+            // results are handled here in order so the program behaves the same
+            let result = match results.get_mut(i).and_then(Option::take) {
+                Some(result) => result,
+                None => {
+                    let logic = stations[i].logic.clone();
+                    logic.procedure(&mut stations[i], ctx)
+                }
+            };
+            match result {
+                Ok(Output::Emit(p)) => {
+                    debug!(ctx.config, 4, "    - produced: {}", p);
+                    observer.on_event(
+                        &Event::PalletProduced {
+                            station: i,
+                            pallet: &p,
+                            side: None,
+                        },
+                        stations,
+                    );
+                    for out_bay in stations[i].out_bays.iter() {
+                        self.moving_pallets.push(MovingPallet::new(
+                            p.clone(),
+                            i,
+                            out_bay,
+                            ctx.config,
                         ));
-                    };
-                    station.clear_in_bays();
-                    continue;
-                } else if station.logic.id == "joint" {
-                    // special case: joint station
-                    for in_bay in station.in_bays.iter() {
-                        if let Some(p) = in_bay {
-                            debug!(4, "    - Produced: {}", p);
-                            for out_bay in station.out_bays.iter() {
-                                moving_pallets.push((p.clone(), *out_bay));
-                            }
-                            break;
-                        }
                     }
-                    station.clear_in_bays();
-                    continue;
-                } else if station.logic.id == "exit" {
-                    // special case: exit
-                    debug!(2, "No remaining moving pallets");
-                    break 'execution_loop;
                 }
-
-                // executing general procedures
-                let procedure = station.logic.procedure;
-                match procedure(&station.in_bays) {
-                    Ok(Some(p)) => {
-                        if !station.logic.output {
-                            return Err(Error::new(
-                                RuntimeError,
-                                station.loc,
-                                "Station procedure returned pallet unexpectedly",
-                            ));
-                        }
-                        debug!(4, "    - produced: {}", p);
-                        for out_bay in station.out_bays.iter() {
-                            moving_pallets.push((p.clone(), *out_bay));
-                        }
-                    }
-                    Ok(None) => {
-                        if station.logic.output
-                            && station.logic.id != "gate"
-                            && station.logic.id != "filter"
-                        {
-                            return Err(Error::new(
-                                RuntimeError,
-                                station.loc,
-                                "Station procedured did not return pallet as expected",
+                Ok(Output::EmitTo(pallets)) => {
+                    for (side, p) in pallets {
+                        debug!(ctx.config, 4, "    - produced: {} ({side})", p);
+                        observer.on_event(
+                            &Event::PalletProduced {
+                                station: i,
+                                pallet: &p,
+                                side: Some(side),
+                            },
+                            stations,
+                        );
+                        for out_bay in stations[i].out_bays.iter().filter(|b| b.side == side) {
+                            self.moving_pallets.push(MovingPallet::new(
+                                p.clone(),
+                                i,
+                                out_bay,
+                                ctx.config,
                             ));
                         }
-                        debug!(4, "    - produced: None",);
-                    }
-                    Err(msg) => {
-                        return Err(Error::new(RuntimeError, station.loc, msg));
                     }
                 }
-
-                station.clear_in_bays();
+                Ok(Output::Drop) => {
+                    debug!(ctx.config, 4, "    - produced: None");
+                    observer.on_event(&Event::PalletDropped { station: i }, stations);
+                }
+                Ok(Output::Halt) => {
+                    debug!(ctx.config, 2, "Halted by #{i}");
+                    observer.on_event(&Event::Halted { station: i }, stations);
+                    self.halted = true;
+                    return Ok(());
+                }
+                Err(msg) => {
+                    self.halted = true;
+                    return Err(Error::new(RuntimeError, stations[i].loc, msg));
+                }
             }
+            stations[i].clear_in_bays();
         }
         debug!(
+            ctx.config,
             3,
-            "Step {step_count} completed ({:.3} ms)",
+            "Step {} completed ({:.3} ms)",
+            self.step_count,
             step_start_t.elapsed().as_secs_f64() * 1000.0
         );
-        step_count += 1;
+        observer.on_event(
+            &Event::StepEnd {
+                step: self.step_count,
+            },
+            stations,
+        );
+        self.step_count += 1;
+        return Ok(());
+    }
+}
+
+/// Runs the procedures of the triggered pure stations, split between as many
+/// threads as are available. Returns the result of each station's procedure by
+/// index, or `None` for stations that still need to run
+fn run_pure_procedures(
+    stations: &mut [Station],
+    config: &Config,
+) -> Vec<Option<Result<Output, String>>> {
+    let mut results: Vec<Option<Result<Output, String>>> =
+        (0..stations.len()).map(|_| None).collect();
+    let mut pure: Vec<(usize, &mut Station)> = stations
+        .iter_mut()
+        .enumerate()
+        .filter(|(_, station)| station.logic.pure() && station.logic.triggered(&station.in_bays))
+        .collect();
+    if pure.len() < 2 {
+        return results;
     }
-    debug!(2, "No remaining moving pallets");
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = pure.len().div_ceil(threads);
+    thread::scope(|scope| {
+        let handles: Vec<_> = pure
+            .chunks_mut(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    // pure stations never use the streams
+                    let mut stdin = io::empty();
+                    let mut stdout = io::sink();
+                    let mut stderr = io::sink();
+                    let mut ctx = Context {
+                        config,
+                        stdin: &mut stdin,
+                        stdout: &mut stdout,
+                        stderr: &mut stderr,
+                        start: Duration::ZERO,
+                        offset: Duration::ZERO,
+                    };
+                    return chunk
+                        .iter_mut()
+                        .map(|(i, station)| {
+                            let logic = station.logic.clone();
+                            return (*i, logic.procedure(station, &mut ctx));
+                        })
+                        .collect::<Vec<_>>();
+                })
+            })
+            .collect();
+        for handle in handles {
+            let chunk_results = handle
+                .join()
+                .unwrap_or_else(|e| std::panic::resume_unwind(e));
+            for (i, result) in chunk_results {
+                results[i] = Some(result);
+            }
+        }
+    });
+    return results;
+}
+
+/// Pallet travelling along a conveyor belt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MovingPallet {
+    pub pallet: Pallet,
+    /// Index of the station that sent the pallet
+    pub origin: usize,
+    /// Destination in the form (station_index, in_bay_index)
+    pub dest: (usize, usize),
+    /// Number of steps before the pallet is delivered, only nonzero with
+    /// physical belts
+    pub delay: usize,
+}
+impl MovingPallet {
+    /// Sends a pallet along the belt from an output bay
+    pub fn new(pallet: Pallet, origin: usize, out_bay: &OutBay, config: &Config) -> Self {
+        return Self {
+            pallet,
+            origin,
+            dest: out_bay.dest,
+            delay: out_bay.delay(config),
+        };
+    }
+}
 
-    return Ok(step_count);
+/// Something that happened while a program was running, stations are referred
+/// to by their index
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<'a> {
+    /// A step is beginning
+    StepStart { step: usize },
+    /// A pallet travelled along a conveyor belt into a station's bay
+    PalletMoved {
+        from: usize,
+        to: usize,
+        bay: usize,
+        pallet: &'a Pallet,
+    },
+    /// A pallet was lost because another pallet entered its bay
+    PalletOverwritten {
+        station: usize,
+        bay: usize,
+        pallet: &'a Pallet,
+    },
+    /// A station's procedure was triggered
+    StationTriggered { station: usize },
+    /// A station produced a pallet, sent out of the output bays on one side, or
+    /// all of them if there is no side
+    PalletProduced {
+        station: usize,
+        pallet: &'a Pallet,
+        side: Option<Direction>,
+    },
+    /// A station consumed its pallets without producing one
+    PalletDropped { station: usize },
+    /// A station stopped the program
+    Halted { station: usize },
+    /// A step has finished
+    StepEnd { step: usize },
+}
+
+/// Receives the events of a running program
+pub trait Observer {
+    /// Handles an event, along with the current state of every station
+    fn on_event(&mut self, event: &Event, stations: &[Station]);
+    /// Whether the observer asks for the run to stop once the current step
+    /// finishes, such as when the user quits an animation
+    fn stop_requested(&self) -> bool {
+        return false;
+    }
+}
+impl Observer for () {
+    fn on_event(&mut self, _: &Event, _: &[Station]) {}
+}
+impl Observer for Vec<&mut dyn Observer> {
+    fn on_event(&mut self, event: &Event, stations: &[Station]) {
+        for observer in self.iter_mut() {
+            observer.on_event(event, stations);
+        }
+    }
+    fn stop_requested(&self) -> bool {
+        return self.iter().any(|observer| observer.stop_requested());
+    }
 }
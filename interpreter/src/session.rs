@@ -0,0 +1,254 @@
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Read},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+/// Every line of input read by a program, along with the step and station it
+/// was read in. Lines are recorded by wrapping the interpreter's input stream
+/// with `record`, and the session observes the run to know which step and
+/// station are reading. Saved sessions can be replayed as the input of a later
+/// run, to reproduce an interactive run exactly
+#[derive(Debug, Clone, Default)]
+pub struct Session {
+    /// Shared with the session's recording input streams
+    recording: Arc<Mutex<Recording>>,
+}
+
+/// Inputs of a session, and the step and station the following reads happen in
+#[derive(Debug, Default)]
+struct Recording {
+    inputs: Vec<SessionInput>,
+    step: usize,
+    station: usize,
+}
+
+/// Line of input read by a station
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionInput {
+    /// Step the line was read in
+    pub step: usize,
+    /// Index of the station that read the line
+    pub station: usize,
+    /// The line, without its newline
+    pub line: String,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    /// Reads a session saved as JSON Lines, one input per line
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut inputs = Vec::new();
+        for line in BufReader::new(fs::File::open(path)?).lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                inputs.push(serde_json::from_str(&line)?);
+            }
+        }
+        let recording = Recording {
+            inputs,
+            ..Recording::default()
+        };
+        return Ok(Self {
+            recording: Arc::new(Mutex::new(recording)),
+        });
+    }
+
+    /// Writes the session as JSON Lines, one input per line
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = String::new();
+        for input in self.inputs().iter() {
+            out += &serde_json::to_string(input)?;
+            out.push('\n');
+        }
+        return fs::write(path, out);
+    }
+
+    /// Lines read so far
+    pub fn inputs(&self) -> Vec<SessionInput> {
+        return self.recording.lock().unwrap().inputs.clone();
+    }
+
+    /// Wraps an input stream, recording every line read from it into this
+    /// session
+    pub fn record<R: BufRead>(&self, stdin: R) -> RecordingStdin<R> {
+        return RecordingStdin {
+            inner: stdin,
+            recording: self.recording.clone(),
+            pending: Vec::new(),
+        };
+    }
+
+    /// Input stream that feeds the session's lines back to a program in order
+    pub fn stdin(&self) -> Vec<u8> {
+        let mut stdin = Vec::new();
+        for input in self.inputs().iter() {
+            stdin.extend_from_slice(input.line.as_bytes());
+            stdin.push(b'\n');
+        }
+        return stdin;
+    }
+
+    /// Index of the first input where a replay of this session differs from
+    /// it, either reading a different line or reading it in a different step or
+    /// station. Returns None if the replay read exactly the same input
+    pub fn divergence(&self, replay: &Session) -> Option<usize> {
+        let (inputs, replayed) = (self.inputs(), replay.inputs());
+        return (0..inputs.len().max(replayed.len())).find(|i| inputs.get(*i) != replayed.get(*i));
+    }
+}
+impl Observer for Session {
+    fn on_event(&mut self, event: &Event, _: &[Station]) {
+        match *event {
+            Event::StepStart { step } => self.recording.lock().unwrap().step = step,
+            Event::StationTriggered { station } => {
+                self.recording.lock().unwrap().station = station;
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Input stream recording the lines read from it into a session, tagged with
+/// the step and station reading them
+pub struct RecordingStdin<R: BufRead> {
+    inner: R,
+    recording: Arc<Mutex<Recording>>,
+    /// Bytes of a line that has only been partly read
+    pending: Vec<u8>,
+}
+impl<R: BufRead> RecordingStdin<R> {
+    /// Records the pending bytes as a line
+    fn push_line(&mut self) {
+        let line = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending.clear();
+        let mut recording = self.recording.lock().unwrap();
+        let (step, station) = (recording.step, recording.station);
+        recording.inputs.push(SessionInput {
+            step,
+            station,
+            line,
+        });
+    }
+}
+impl<R: BufRead> Read for RecordingStdin<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let buf = self.fill_buf()?;
+        let n = buf.len().min(out.len());
+        out[..n].copy_from_slice(&buf[..n]);
+        self.consume(n);
+        return Ok(n);
+    }
+}
+impl<R: BufRead> BufRead for RecordingStdin<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        // the last line may not end with a newline
+        if self.inner.fill_buf()?.is_empty() && !self.pending.is_empty() {
+            self.push_line();
+        }
+        return self.inner.fill_buf();
+    }
+    fn consume(&mut self, amt: usize) {
+        if let Ok(buf) = self.inner.fill_buf() {
+            let consumed = buf[..amt.min(buf.len())].to_vec();
+            for byte in consumed {
+                if byte == b'\n' {
+                    self.push_line();
+                } else {
+                    self.pending.push(byte);
+                }
+            }
+        }
+        self.inner.consume(amt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: &str = include_str!("../../examples/greet.factory");
+
+    /// Runs the greet example with some input, recording a session
+    fn record(stdin: &[u8]) -> (Vec<u8>, Session) {
+        let program = Program::new(SRC).unwrap();
+        let mut output: Vec<u8> = Vec::new();
+        let mut session = Session::new();
+        Interpreter::new()
+            .stdin(stdin)
+            .record_stdin(&session)
+            .stdout(&mut output)
+            .run_observed(&program, &mut session)
+            .unwrap();
+        return (output, session);
+    }
+
+    #[test]
+    fn test_record_replay() {
+        let (output, session) = record(b"Jaxson\n");
+        assert_eq!(session.inputs().len(), 1);
+        assert_eq!(session.inputs()[0].line, "Jaxson");
+
+        let (replay_output, replay) = record(&session.stdin());
+        assert_eq!(replay_output, output);
+        assert_eq!(session.divergence(&replay), None);
+    }
+
+    #[test]
+    fn test_divergence() {
+        let (_, session) = record(b"Jaxson\n");
+        let (_, other) = record(b"Sam\n");
+        assert_eq!(session.divergence(&other), Some(0));
+        assert_eq!(session.divergence(&Session::new()), Some(0));
+    }
+
+    #[test]
+    fn test_record_host_station() {
+        let mut ns = Namespace::builtin();
+        ns.register(FnStation::new("ask", 1, |_, ctx| {
+            let mut line = String::new();
+            ctx.stdin.read_line(&mut line).map_err(|e| e.to_string())?;
+            return Ok(Output::Emit(Pallet::String(line)));
+        }));
+        let program = Program::with_namespace("[start]═─[ask]═─[println]", &ns).unwrap();
+        let mut session = Session::new();
+        Interpreter::new()
+            .stdin("first\nsecond".as_bytes())
+            .record_stdin(&session)
+            .stdout(Vec::new())
+            .run_observed(&program, &mut session)
+            .unwrap();
+        let ask = program
+            .stations()
+            .iter()
+            .position(|station| station.logic.id() == "ask")
+            .unwrap();
+        assert_eq!(
+            session.inputs(),
+            vec![SessionInput {
+                step: 0,
+                station: ask,
+                line: String::from("first"),
+            }]
+        );
+
+        // the last line is recorded even without a newline
+        let session = Session::new();
+        let mut all = String::new();
+        session
+            .record("a\nb".as_bytes())
+            .read_to_string(&mut all)
+            .unwrap();
+        assert_eq!(all, "a\nb");
+        let lines: Vec<String> = session.inputs().into_iter().map(|i| i.line).collect();
+        assert_eq!(lines, vec!["a", "b"]);
+    }
+}
@@ -0,0 +1,200 @@
+use std::{fs, io, path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::*;
+
+/// State of a running program at a step boundary, which can be saved to a file
+/// and resumed later. Only the parts of the program that change while it runs
+/// are saved, resuming preprocesses the source file again, which must not have
+/// changed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Path of the source file the program was preprocessed from
+    pub source: String,
+    /// SHA-256 hash of the source file, in hex
+    pub source_hash: String,
+    /// Whether pallets take a step to travel along each cell of a belt
+    pub physical_belts: bool,
+    /// Seed for the random stations that haven't been triggered yet
+    pub seed: u64,
+    /// Number of completed steps
+    pub step_count: usize,
+    /// Time the program had been running for, which time stations carry on
+    /// from
+    pub elapsed: Duration,
+    /// Input bays and memory of each station
+    pub stations: Vec<StationSnapshot>,
+    /// All pallets travelling along conveyor belts
+    pub moving_pallets: Vec<MovingPallet>,
+}
+
+/// Parts of a station that change while the program runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StationSnapshot {
+    pub in_bays: Vec<Option<Pallet>>,
+    pub state: Option<Pallet>,
+}
+
+impl Snapshot {
+    /// Captures the state of a runtime between steps, along with the source it
+    /// was preprocessed from
+    pub fn capture(runtime: &Runtime, source: &str, src: &str, config: &Config) -> Self {
+        return Self {
+            source: source.to_owned(),
+            source_hash: source_hash(src),
+            physical_belts: config.physical_belts,
+            seed: config.seed,
+            step_count: runtime.step_count,
+            elapsed: runtime.elapsed,
+            stations: runtime
+                .stations
+                .iter()
+                .map(|station| StationSnapshot {
+                    in_bays: station.in_bays.clone(),
+                    state: station.state.clone(),
+                })
+                .collect(),
+            moving_pallets: runtime.moving_pallets.clone(),
+        };
+    }
+
+    /// Restores a runtime for the program preprocessed from the snapshot's
+    /// source. Fails if the source has changed since the snapshot was taken
+    pub fn restore(&self, program: &Program, src: &str) -> Result<Runtime, String> {
+        if source_hash(src) != self.source_hash {
+            return Err(format!(
+                "\"{}\" has changed since the snapshot was taken",
+                self.source
+            ));
+        }
+        let mut stations = program.stations().clone();
+        if stations.len() != self.stations.len() {
+            return Err(format!(
+                "Snapshot has {} stations, but the program has {}",
+                self.stations.len(),
+                stations.len()
+            ));
+        }
+        for (station, saved) in stations.iter_mut().zip(self.stations.iter()) {
+            if station.in_bays.len() != saved.in_bays.len() {
+                return Err(format!(
+                    "Snapshot doesn't match the bays of the station at {}",
+                    station.loc
+                ));
+            }
+            station.in_bays = saved.in_bays.clone();
+            station.state = saved.state.clone();
+        }
+        for moving in self.moving_pallets.iter() {
+            let (dest, bay) = moving.dest;
+            if moving.origin >= stations.len()
+                || dest >= stations.len()
+                || bay >= stations[dest].in_bays.len()
+            {
+                return Err(format!(
+                    "Snapshot has a pallet travelling from #{} to #{dest}:{bay}, which the \
+                     program doesn't have",
+                    moving.origin
+                ));
+            }
+        }
+        return Ok(Runtime::resume(
+            stations,
+            self.moving_pallets.clone(),
+            self.step_count,
+            self.elapsed,
+        ));
+    }
+
+    /// Reads a snapshot from a JSON file
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        return Ok(serde_json::from_str(&fs::read_to_string(path)?)?);
+    }
+
+    /// Writes the snapshot to a file as JSON
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        return fs::write(path, serde_json::to_string(self)?);
+    }
+}
+
+/// SHA-256 hash of source code, in hex
+pub fn source_hash(src: &str) -> String {
+    return Sha256::digest(src.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: &str = include_str!("../../examples/fibonacci.factory");
+
+    #[test]
+    fn test_snapshot_resume() {
+        let program = Program::new(SRC).unwrap();
+        let mut full_output: Vec<u8> = Vec::new();
+        let full_report = Interpreter::new()
+            .stdout(&mut full_output)
+            .run(&program)
+            .unwrap();
+
+        let mut output: Vec<u8> = Vec::new();
+        let mut interpreter = Interpreter::new().stdout(&mut output);
+        let (_, runtime) = interpreter
+            .run_until(&program, None, &mut (), &mut |runtime| {
+                runtime.step_count == 20
+            })
+            .unwrap();
+        let json = serde_json::to_string(&Snapshot::capture(
+            &runtime.unwrap(),
+            "fibonacci.factory",
+            SRC,
+            &Config::default(),
+        ))
+        .unwrap();
+        drop(interpreter);
+
+        let snapshot: Snapshot = serde_json::from_str(&json).unwrap();
+        let runtime = snapshot.restore(&program, SRC).unwrap();
+        let mut interpreter = Interpreter::new().stdout(&mut output);
+        let (report, runtime) = interpreter
+            .run_until(&program, Some(runtime), &mut (), &mut |_| false)
+            .unwrap();
+        drop(interpreter);
+        assert!(runtime.is_none());
+        assert_eq!(report.steps, full_report.steps);
+        assert_eq!(output, full_output);
+    }
+
+    #[test]
+    fn test_snapshot_changed_source() {
+        let program = Program::new(SRC).unwrap();
+        let runtime = Runtime::resume(program.stations().clone(), Vec::new(), 0, Duration::ZERO);
+        let snapshot = Snapshot::capture(&runtime, "fibonacci.factory", SRC, &Config::default());
+        let changed = SRC.replace("{92}", "{90}");
+        assert!(snapshot.restore(&program, &changed).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_invalid_pallet() {
+        let program = Program::new(SRC).unwrap();
+        let runtime = Runtime::resume(program.stations().clone(), Vec::new(), 0, Duration::ZERO);
+        let mut snapshot =
+            Snapshot::capture(&runtime, "fibonacci.factory", SRC, &Config::default());
+        let n = program.stations().len();
+        for (origin, dest) in [(0, (n, 0)), (0, (0, 4)), (n, (0, 0))] {
+            snapshot.moving_pallets = vec![MovingPallet {
+                pallet: Pallet::Empty,
+                origin,
+                dest,
+                delay: 0,
+            }];
+            let err = snapshot.restore(&program, SRC).unwrap_err();
+            assert!(err.contains("which the program doesn't have"), "{err}");
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::io::{self, Write};
+
+use serde_json::{json, Value};
+
+use crate::*;
+
+/// Observer that writes every event of a run as a line of JSON (JSON Lines),
+/// for inspecting a run with external tools
+pub struct TraceWriter<W: Write> {
+    out: W,
+    /// Step the following events happen in
+    step: usize,
+    /// First error encountered while writing, later events are skipped
+    error: Option<io::Error>,
+}
+impl<W: Write> TraceWriter<W> {
+    pub fn new(out: W) -> Self {
+        return Self {
+            out,
+            step: 0,
+            error: None,
+        };
+    }
+
+    /// Flushes the trace, returning the first error encountered while writing
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        self.out.flush()?;
+        return Ok(self.out);
+    }
+}
+impl<W: Write> Observer for TraceWriter<W> {
+    fn on_event(&mut self, event: &Event, stations: &[Station]) {
+        if self.error.is_some() {
+            return;
+        }
+        let line = match *event {
+            Event::StepStart { step } => {
+                self.step = step;
+                json!({ "step": step, "event": "step_start" })
+            }
+            Event::PalletMoved {
+                from,
+                to,
+                bay,
+                pallet,
+            } => json!({
+                "step": self.step,
+                "event": "moved",
+                "from": station_json(stations, from),
+                "to": station_json(stations, to),
+                "bay": bay,
+                "pallet": pallet,
+            }),
+            Event::PalletOverwritten {
+                station,
+                bay,
+                pallet,
+            } => json!({
+                "step": self.step,
+                "event": "overwritten",
+                "station": station_json(stations, station),
+                "bay": bay,
+                "pallet": pallet,
+            }),
+            Event::StationTriggered { station } => json!({
+                "step": self.step,
+                "event": "triggered",
+                "station": station_json(stations, station),
+                "in_bays": stations[station].in_bays,
+            }),
+            Event::PalletProduced {
+                station,
+                pallet,
+                side,
+            } => json!({
+                "step": self.step,
+                "event": "produced",
+                "station": station_json(stations, station),
+                "pallet": pallet,
+                "side": side,
+            }),
+            Event::PalletDropped { station } => json!({
+                "step": self.step,
+                "event": "dropped",
+                "station": station_json(stations, station),
+            }),
+            Event::Halted { station } => json!({
+                "step": self.step,
+                "event": "halted",
+                "station": station_json(stations, station),
+            }),
+            Event::StepEnd { step } => json!({ "step": step, "event": "step_end" }),
+        };
+        if let Err(e) = writeln!(self.out, "{line}") {
+            self.error = Some(e);
+        }
+    }
+}
+
+/// Describes a station by its index, identifier, and 1-indexed source location
+fn station_json(stations: &[Station], i: usize) -> Value {
+    let station = &stations[i];
+    return json!({
+        "index": i,
+        "id": station.logic.id(),
+        "line": station.loc.pos.line + 1,
+        "col": station.loc.pos.col,
+        "len": station.loc.len,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace() {
+        let program = Program::new("[start]═─{2}═─[+]═─[println]\n   ╚─{3}═──────┘").unwrap();
+        let mut trace = TraceWriter::new(Vec::new());
+        Interpreter::new()
+            .stdout(io::sink())
+            .run_observed(&program, &mut trace)
+            .unwrap();
+        let out = String::from_utf8(trace.finish().unwrap()).unwrap();
+        let lines: Vec<Value> = out
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+
+        assert_eq!(lines[0]["event"], "produced");
+        assert_eq!(lines[0]["station"]["id"], "start");
+        assert_eq!(lines[0]["pallet"], "Empty");
+        assert_eq!(lines[1], json!({ "step": 0, "event": "step_start" }));
+        let sum = lines
+            .iter()
+            .find(|l| l["event"] == "produced" && l["station"]["id"] == "add")
+            .unwrap();
+        assert_eq!(sum["pallet"], json!({ "Int": 5 }));
+        assert_eq!(sum["station"]["line"], 1);
+        let printed = lines
+            .iter()
+            .find(|l| l["event"] == "moved" && l["to"]["id"] == "println")
+            .unwrap();
+        assert_eq!(printed["from"]["id"], "add");
+        assert_eq!(printed["bay"], 0);
+        assert_eq!(lines.last().unwrap()["event"], "step_end");
+    }
+}
@@ -0,0 +1,267 @@
+use std::sync::Arc;
+
+use crate::*;
+
+/// Support code copied into every transpiled program: pallets, the builtin
+/// station procedures and the step loop
+const PRELUDE: &str = include_str!("prelude.rs");
+
+/// Transpiles a program into the source code of a standalone Rust program that
+/// runs it with the same steps and output, and compiles with plain `rustc`.
+/// Belts take as long as they would running with the configuration, random
+/// stations use its seed, and unless disabled by it the program is optimized
+/// first. Fails if the program uses a
+/// station type or literal that isn't builtin
+pub fn transpile(program: &Program, name: &str, config: &Config) -> Result<String, Error> {
+    let stations = if config.optimize {
+        optimizer::optimize(program.stations().clone(), config)
+    } else {
+        program.stations().clone()
+    };
+
+    let mut bays = Vec::new();
+    let mut inputs = Vec::new();
+    let mut fanout = Vec::new();
+    let mut targets = Vec::new();
+    let mut locations = Vec::new();
+    let mut spawns = Vec::new();
+    let mut spawn_arms = String::new();
+    let mut fire_arms = String::new();
+
+    let mut first_slots = Vec::with_capacity(stations.len());
+    let mut slots = 0;
+    for station in stations.iter() {
+        first_slots.push(slots);
+        slots += station.in_bays.len();
+    }
+    for (i, station) in stations.iter().enumerate() {
+        let is_builtin = STATION_TYPES
+            .get(station.logic.id())
+            .is_some_and(|builtin| Arc::ptr_eq(builtin, &station.logic));
+        if !is_builtin {
+            return Err(Error::new(
+                IdentifierError,
+                station.loc,
+                format!(
+                    "Station type \"{}\" isn't builtin, so can't be transpiled",
+                    station.logic.id()
+                ),
+            ));
+        }
+
+        bays.push(format!(
+            "({}, {})",
+            first_slots[i],
+            first_slots[i] + station.in_bays.len()
+        ));
+        inputs.push(station.logic.inputs().to_string());
+        fanout.push(format!(
+            "({}, {})",
+            targets.len(),
+            targets.len() + station.out_bays.len()
+        ));
+        for out_bay in station.out_bays.iter() {
+            let (dest, bay) = out_bay.dest;
+            targets.push(format!(
+                "Target {{ slot: {}, station: {dest}, side: Direction::{}, delay: {} }}",
+                first_slots[dest] + bay,
+                direction(out_bay.side),
+                out_bay.delay(config)
+            ));
+        }
+        locations.push(format!("{:?}", station.loc.to_string()));
+        if let Some(p) = station.logic.spawn() {
+            spawns.push(i.to_string());
+            spawn_arms += &format!("        {i} => {},\n", literal(&p, station)?);
+        }
+        if station.logic.inputs() > 0 {
+            fire_arms += &format!("        {i} => {},\n", procedure_call(station)?);
+        }
+    }
+    debug!(
+        config,
+        2,
+        "Transpiled {} stations, {} slots, {} belts",
+        stations.len(),
+        slots,
+        targets.len()
+    );
+
+    return Ok(format!(
+        "// Built by `factory build` from {name}
+{PRELUDE}
+const LAYOUT: Layout = Layout {{
+    bays: &[{}],
+    inputs: &[{}],
+    fanout: &[{}],
+    targets: &[
+        {}
+    ],
+    locations: &[{}],
+    spawns: &[{}],
+}};
+
+const SEED: u64 = {};
+
+fn spawn(station: usize) -> Pallet {{
+    match station {{
+{spawn_arms}        _ => unreachable!(),
+    }}
+}}
+
+fn fire(
+    station: usize,
+    bays: &mut [Option<Pallet>],
+    state: &mut Option<Pallet>,
+    io: &mut Streams,
+) -> Result<Output, String> {{
+    match station {{
+{fire_arms}        _ => unreachable!(),
+    }}
+}}
+",
+        bays.join(", "),
+        inputs.join(", "),
+        fanout.join(", "),
+        targets.join(",\n        "),
+        locations.join(", "),
+        spawns.join(", "),
+        config.seed,
+    ));
+}
+
+/// Name of a direction's variant in the prelude
+fn direction(side: Direction) -> &'static str {
+    return match side {
+        Direction::NORTH => "North",
+        Direction::EAST => "East",
+        Direction::SOUTH => "South",
+        Direction::WEST => "West",
+    };
+}
+
+/// Rust expression creating a pallet, or an error if the prelude can't hold it
+fn literal(pallet: &Pallet, station: &Station) -> Result<String, Error> {
+    return match pallet {
+        Pallet::Empty => Ok(String::from("Pallet::Empty")),
+        Pallet::Bool(b) => Ok(format!("Pallet::Bool({b})")),
+        Pallet::Char(c) => Ok(format!("Pallet::Char({c:?})")),
+        Pallet::String(s) => Ok(format!("Pallet::String(String::from({s:?}))")),
+        Pallet::Int(i) => Ok(format!("Pallet::Int({i})")),
+        Pallet::Float(f) => Ok(format!("Pallet::Float(f64::from_bits({:#x}))", f.to_bits())),
+        Pallet::List(items) => {
+            let items = items
+                .iter()
+                .map(|p| literal(p, station))
+                .collect::<Result<Vec<String>, Error>>()?;
+            Ok(format!("Pallet::List(vec![{}])", items.join(", ")))
+        }
+        Pallet::Map(entries) => {
+            let entries = entries
+                .iter()
+                .map(|(k, v)| {
+                    Ok(format!(
+                        "({}, {})",
+                        literal(k, station)?,
+                        literal(v, station)?
+                    ))
+                })
+                .collect::<Result<Vec<String>, Error>>()?;
+            Ok(format!("Pallet::Map(vec![{}])", entries.join(", ")))
+        }
+        #[allow(unreachable_patterns)]
+        _ => Err(Error::new(
+            SyntaxError,
+            station.loc,
+            format!("{pallet} can't be transpiled"),
+        )),
+    };
+}
+
+/// Rust expression running a builtin station's procedure, in terms of the
+/// prelude's functions and the arguments of `fire`
+fn procedure_call(station: &Station) -> Result<String, Error> {
+    let call = match station.logic.id() {
+        "exit" => String::from("Ok(Output::Halt)"),
+        "joint" => String::from("joint(bays)"),
+        "assign" => match &station.config {
+            Some(p) => format!("Ok(Output::Emit({}))", literal(p, station)?),
+            None => String::from("Err(String::from(\"Assign station is missing its literal\"))"),
+        },
+        "gate" | "filter" | "branch" => format!("{}(bays)", station.logic.id()),
+        "print" => String::from("print(bays, io, false)"),
+        "println" => String::from("print(bays, io, true)"),
+        "readln" => String::from("readln(io)"),
+        "sleep" => String::from("sleep(bays, io)"),
+        "clock" => String::from("clock(io)"),
+        "now" => String::from("now()"),
+        "count" => String::from("count(bays, state)"),
+        "acc" => String::from("accumulate(bays, state)"),
+        "latch" => String::from("latch(bays, state)"),
+        "mod" => String::from("modulo(bays).map(Output::Emit)"),
+        "pop" => String::from("pop(bays)"),
+        "lookup" => String::from("lookup(bays)"),
+        id @ ("int" | "float" | "str" | "char" | "bool") => {
+            format!("to_{id}(bays).map(Output::Emit)")
+        }
+        "type" => String::from("type_of(bays).map(Output::Emit)"),
+        id @ ("rand" | "randint" | "shuffle") => {
+            let pos = station.loc.pos;
+            format!("{id}(bays, state, {}, {})", pos.line, pos.col)
+        }
+        id @ ("add" | "sub" | "mult" | "div" | "eq" | "ne" | "gt" | "lt" | "gte" | "lte"
+        | "inc" | "dec" | "and" | "or" | "not" | "push" | "get" | "set" | "len"
+        | "concat" | "slice" | "sort" | "insert" | "remove" | "keys" | "size" | "charat"
+        | "substr" | "split" | "join" | "upper" | "lower" | "trim" | "contains"
+        | "startswith" | "replace" | "find") => {
+            format!("{id}(bays).map(Output::Emit)")
+        }
+        id => {
+            return Err(Error::new(
+                IdentifierError,
+                station.loc,
+                format!("Station type \"{id}\" can't be transpiled yet"),
+            ));
+        }
+    };
+    return Ok(call);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transpile() {
+        let program = Program::new("[start]═─{\"hi\"}═─[println]").unwrap();
+        let config = Config::default().with_optimize(false);
+        let src = transpile(&program, "hi.factory", &config).unwrap();
+        assert!(src.starts_with("// Built by `factory build` from hi.factory\n"));
+        assert!(src.contains("        0 => Pallet::Empty,\n"));
+        assert!(
+            src.contains("        1 => Ok(Output::Emit(Pallet::String(String::from(\"hi\")))),\n")
+        );
+        assert!(src.contains("        2 => print(bays, io, true),\n"));
+        assert!(src.contains("    bays: &[(0, 0), (0, 1), (1, 2)],\n"));
+    }
+
+    #[test]
+    fn test_transpile_list() {
+        let program = Program::new("[start]═─{[1, [\"a\"]]}═─[pop]═─[println]").unwrap();
+        let config = Config::default().with_optimize(false);
+        let src = transpile(&program, "list.factory", &config).unwrap();
+        assert!(src.contains(
+            "Pallet::List(vec![Pallet::Int(1), Pallet::List(vec![Pallet::String(String::from(\"a\"))])])"
+        ));
+        assert!(src.contains("        2 => pop(bays),\n"));
+    }
+
+    #[test]
+    fn test_transpile_host_station() {
+        let mut ns = Namespace::builtin();
+        ns.register(FnStation::new("metric", 1, |_, _| Ok(Output::Drop)));
+        let program = Program::with_namespace("[start]═─[metric]", &ns).unwrap();
+        let err = transpile(&program, "metric.factory", &Config::default()).unwrap_err();
+        assert_eq!(err.loc.pos.col, 9);
+    }
+}
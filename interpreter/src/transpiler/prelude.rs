@@ -0,0 +1,1186 @@
+// Support code copied into every program built by `factory build`. It mirrors
+// the interpreter's pallets, builtin station procedures and step semantics, and
+// only depends on the standard library so the output compiles with plain rustc
+#![allow(dead_code, unused_variables)]
+
+use std::convert::TryFrom;
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Pallet {
+    Empty,
+    Bool(bool),
+    Char(char),
+    String(String),
+    Int(i64),
+    Float(f64),
+    List(Vec<Pallet>),
+    /// Entries sorted by key
+    Map(Vec<(Pallet, Pallet)>),
+}
+impl Pallet {
+    /// The pallet's value as print stations write it
+    fn text(&self) -> String {
+        return match self {
+            Pallet::Empty => String::new(),
+            Pallet::Char(c) => c.to_string(),
+            Pallet::String(s) => s.clone(),
+            Pallet::Float(f) => f.to_string(),
+            _ => self.literal(),
+        };
+    }
+
+    fn type_name(&self) -> &'static str {
+        return match self {
+            Pallet::Empty => "empty",
+            Pallet::Bool(_) => "bool",
+            Pallet::Char(_) => "char",
+            Pallet::String(_) => "string",
+            Pallet::Int(_) => "int",
+            Pallet::Float(_) => "float",
+            Pallet::List(_) => "list",
+            Pallet::Map(_) => "map",
+        };
+    }
+
+    /// The pallet's value written as an assign station literal, which is how
+    /// the values inside lists and maps are printed
+    fn literal(&self) -> String {
+        return match self {
+            Pallet::Empty => String::new(),
+            Pallet::Bool(b) => b.to_string(),
+            Pallet::Char(c) => format!("'{c}'"),
+            Pallet::String(s) => format!("\"{s}\""),
+            Pallet::Int(i) => i.to_string(),
+            Pallet::Float(f) => format!("{f:?}"),
+            Pallet::List(items) => {
+                let items: Vec<String> = items.iter().map(|p| p.literal()).collect();
+                format!("[{}]", items.join(", "))
+            }
+            Pallet::Map(entries) if entries.is_empty() => String::from("[:]"),
+            Pallet::Map(entries) => {
+                let entries: Vec<String> = entries
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", k.literal(), v.literal()))
+                    .collect();
+                format!("[{}]", entries.join(", "))
+            }
+        };
+    }
+}
+impl std::fmt::Display for Pallet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pallet::Empty => write!(f, "Pallet< >"),
+            Pallet::Bool(b) => write!(f, "Pallet<b:{b}>"),
+            Pallet::Char(c) => write!(f, "Pallet<c:'{c}'>"),
+            Pallet::String(s) => write!(f, "Pallet<s:\"{s}\">"),
+            Pallet::Int(i) => write!(f, "Pallet<i:{i}>"),
+            Pallet::Float(x) => write!(f, "Pallet<f:{x}>"),
+            Pallet::List(_) => write!(f, "Pallet<l:{}>", self.literal()),
+            Pallet::Map(_) => write!(f, "Pallet<m:{}>", self.literal()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+/// Result of a station's procedure
+enum Output {
+    Emit(Pallet),
+    EmitTo(Vec<(Direction, Pallet)>),
+    Drop,
+    Halt,
+}
+
+/// Belt leading into a station's input bay
+struct Target {
+    slot: usize,
+    station: usize,
+    side: Direction,
+    delay: usize,
+}
+
+/// Pallet travelling along a belt, as the index of its target
+struct Moving {
+    target: usize,
+    delay: usize,
+    pallet: Pallet,
+}
+
+/// Layout of the program's stations and belts
+struct Layout {
+    /// Slots of each station's input bays
+    bays: &'static [(usize, usize)],
+    /// Minimum number of inputs of each station
+    inputs: &'static [usize],
+    /// Targets of the belts leaving each station
+    fanout: &'static [(usize, usize)],
+    targets: &'static [Target],
+    /// Location of each station in the source code
+    locations: &'static [&'static str],
+    /// Stations that spawn a pallet when the program begins, their pallets come
+    /// from `spawn`
+    spawns: &'static [usize],
+}
+
+/// Streams station procedures read from and write to, and when the program
+/// started running
+struct Streams<'a> {
+    stdin: io::StdinLock<'a>,
+    stdout: io::BufWriter<io::StdoutLock<'a>>,
+    start: Instant,
+}
+
+type Fire =
+    fn(usize, &mut [Option<Pallet>], &mut Option<Pallet>, &mut Streams) -> Result<Output, String>;
+
+fn send(
+    layout: &Layout,
+    station: usize,
+    side: Option<Direction>,
+    p: Pallet,
+    moving: &mut Vec<Moving>,
+) {
+    let (start, end) = layout.fanout[station];
+    for target in start..end {
+        if side.is_none_or(|side| side == layout.targets[target].side) {
+            moving.push(Moving {
+                target,
+                delay: layout.targets[target].delay,
+                pallet: p.clone(),
+            });
+        }
+    }
+}
+
+/// Runs the program to completion, returns the number of steps or the location
+/// and message of a runtime error
+fn run(layout: &Layout, fire: Fire, io: &mut Streams) -> Result<usize, (usize, String)> {
+    let mut slots: Vec<Option<Pallet>> = vec![None; layout.bays.last().map_or(0, |b| b.1)];
+    let mut states: Vec<Option<Pallet>> = vec![None; layout.bays.len()];
+    let mut moving: Vec<Moving> = Vec::new();
+    let mut arriving: Vec<Moving> = Vec::new();
+    let mut touched: Vec<usize> = Vec::new();
+    let mut is_touched = vec![false; layout.bays.len()];
+    for station in layout.spawns.iter() {
+        send(layout, *station, None, spawn(*station), &mut moving);
+    }
+
+    let mut step_count = 0;
+    while !moving.is_empty() {
+        for m in std::mem::take(&mut moving) {
+            if m.delay == 0 {
+                arriving.push(m);
+            } else {
+                moving.push(Moving {
+                    delay: m.delay - 1,
+                    ..m
+                });
+            }
+        }
+        for m in arriving.drain(..) {
+            let target = &layout.targets[m.target];
+            slots[target.slot] = Some(m.pallet);
+            if !is_touched[target.station] {
+                is_touched[target.station] = true;
+                touched.push(target.station);
+            }
+        }
+
+        touched.sort_unstable();
+        for i in touched.drain(..) {
+            is_touched[i] = false;
+            let bays = &mut slots[layout.bays[i].0..layout.bays[i].1];
+            let inputs = layout.inputs[i];
+            if inputs == 0 || bays.iter().filter(|b| b.is_some()).count() < inputs {
+                continue;
+            }
+            let output = fire(i, bays, &mut states[i], io);
+            for bay in bays.iter_mut() {
+                *bay = None;
+            }
+            match output {
+                Ok(Output::Emit(p)) => send(layout, i, None, p, &mut moving),
+                Ok(Output::EmitTo(pallets)) => {
+                    for (side, p) in pallets {
+                        send(layout, i, Some(side), p, &mut moving);
+                    }
+                }
+                Ok(Output::Drop) => {}
+                Ok(Output::Halt) => return Ok(step_count),
+                Err(msg) => return Err((i, msg)),
+            }
+        }
+        step_count += 1;
+    }
+    return Ok(step_count);
+}
+
+fn main() -> ExitCode {
+    let mut io = Streams {
+        stdin: io::stdin().lock(),
+        stdout: io::BufWriter::new(io::stdout().lock()),
+        start: Instant::now(),
+    };
+    let result = run(&LAYOUT, fire, &mut io);
+    let _ = io.stdout.flush();
+    match result {
+        Ok(_) => return ExitCode::SUCCESS,
+        Err((station, msg)) => {
+            eprintln!("Runtime Error @ {}\n{}", LAYOUT.locations[station], msg);
+            return ExitCode::FAILURE;
+        }
+    }
+}
+
+fn list_pallets(pallets: &[Option<Pallet>]) -> String {
+    let list: Vec<String> = pallets
+        .iter()
+        .map(|p| match p {
+            Some(p) => p.to_string(),
+            None => String::from("None"),
+        })
+        .collect();
+    return format!("({})", list.join(", "));
+}
+
+fn joint(bays: &mut [Option<Pallet>]) -> Result<Output, String> {
+    match bays.iter_mut().find_map(Option::take) {
+        Some(p) => Ok(Output::Emit(p)),
+        None => Err(String::from("Expected pallet, received none")),
+    }
+}
+
+fn gate(bays: &[Option<Pallet>]) -> Result<Output, String> {
+    match (&bays[0], &bays[1]) {
+        (Some(Pallet::Bool(b)), Some(p)) | (Some(p), Some(Pallet::Bool(b))) => Ok(if *b {
+            Output::Emit(p.clone())
+        } else {
+            Output::Drop
+        }),
+        _ => Err(format!(
+            "Expected at least one boolean pallet, received {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn filter(bays: &[Option<Pallet>]) -> Result<Output, String> {
+    match &bays[0] {
+        Some(Pallet::Bool(false)) => Ok(Output::Drop),
+        Some(p) => Ok(Output::Emit(p.clone())),
+        _ => Err(format!(
+            "Expected pallet, received {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn branch(bays: &[Option<Pallet>]) -> Result<Output, String> {
+    match (&bays[0], &bays[1]) {
+        (Some(Pallet::Bool(b)), Some(p)) | (Some(p), Some(Pallet::Bool(b))) => {
+            let side = if *b { Direction::East } else { Direction::West };
+            Ok(Output::EmitTo(vec![(side, p.clone())]))
+        }
+        _ => Err(format!(
+            "Expected at least one boolean pallet, received {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn print(bays: &[Option<Pallet>], io: &mut Streams, newline: bool) -> Result<Output, String> {
+    let result = match &bays[0] {
+        Some(p) => write!(io.stdout, "{}", p.text()),
+        None => return Err(String::from("Missing pallet in print")),
+    };
+    result.map_err(|e| e.to_string())?;
+    if newline {
+        writeln!(io.stdout).map_err(|e| e.to_string())?;
+    }
+    return Ok(Output::Drop);
+}
+
+fn readln(io: &mut Streams) -> Result<Output, String> {
+    let _ = io.stdout.flush();
+    let mut input = String::new();
+    io.stdin.read_line(&mut input).map_err(|e| e.to_string())?;
+    if input.ends_with('\n') {
+        input.pop();
+    }
+    return Ok(Output::Emit(Pallet::String(input)));
+}
+
+fn count(bays: &[Option<Pallet>], state: &mut Option<Pallet>) -> Result<Output, String> {
+    let received = bays.iter().flatten().count() as i64;
+    let count = match state {
+        Some(Pallet::Int(count)) => *count + received,
+        _ => received,
+    };
+    *state = Some(Pallet::Int(count));
+    return Ok(Output::Emit(Pallet::Int(count)));
+}
+
+fn accumulate(bays: &[Option<Pallet>], state: &mut Option<Pallet>) -> Result<Output, String> {
+    let mut sum = state.take();
+    for p in bays.iter().flatten() {
+        sum = match sum {
+            Some(sum) => Some(add(&[Some(sum), Some(p.clone())])?),
+            None => Some(p.clone()),
+        };
+    }
+    *state = sum.clone();
+    match sum {
+        Some(sum) => Ok(Output::Emit(sum)),
+        None => Err(String::from("Expected pallet, received none")),
+    }
+}
+
+fn latch(bays: &[Option<Pallet>], state: &mut Option<Pallet>) -> Result<Output, String> {
+    if bays.len() != 2 {
+        return Err(format!(
+            "Expected a value bay and a trigger bay, found {} bay(s)",
+            bays.len()
+        ));
+    }
+    if let Some(p) = &bays[0] {
+        *state = Some(p.clone());
+    }
+    if bays[1].is_none() {
+        return Ok(Output::Drop);
+    }
+    match state {
+        Some(p) => Ok(Output::Emit(p.clone())),
+        None => Ok(Output::Drop),
+    }
+}
+
+fn integer_arithmetic(
+    op: fn(i64, i64) -> Option<i64>,
+    divides: bool,
+    a: i64,
+    b: i64,
+) -> Result<Pallet, String> {
+    if divides && b == 0 {
+        return Err(String::from("Attempted divide by zero"));
+    }
+    match op(a, b) {
+        Some(n) => Ok(Pallet::Int(n)),
+        None => Err(format!(
+            "Integer overflow, {} and {} can't be combined",
+            Pallet::Int(a),
+            Pallet::Int(b)
+        )),
+    }
+}
+
+fn add(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match (&bays[0], &bays[1]) {
+        (Some(Pallet::Int(a)), Some(Pallet::Int(b))) => {
+            integer_arithmetic(i64::checked_add, false, *a, *b)
+        }
+        (Some(Pallet::Float(a)), Some(Pallet::Float(b))) => Ok(Pallet::Float(a + b)),
+        (Some(Pallet::String(s)), Some(Pallet::Char(c))) => Ok(Pallet::String(format!("{s}{c}"))),
+        (Some(Pallet::String(a)), Some(Pallet::String(b))) => Ok(Pallet::String(format!("{a}{b}"))),
+        _ => Err(format!(
+            "Unexpected pallet types received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn arithmetic(
+    bays: &[Option<Pallet>],
+    op: fn(i64, i64) -> Option<i64>,
+    float_op: fn(f64, f64) -> f64,
+    divides: bool,
+) -> Result<Pallet, String> {
+    match (&bays[0], &bays[1]) {
+        (Some(Pallet::Int(a)), Some(Pallet::Int(b))) => integer_arithmetic(op, divides, *a, *b),
+        (Some(Pallet::Float(a)), Some(Pallet::Float(b))) => {
+            if divides && *b == 0.0 {
+                return Err(String::from("Attempted divide by zero"));
+            }
+            Ok(Pallet::Float(float_op(*a, *b)))
+        }
+        _ => Err(format!(
+            "Expected numerical pallets, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn sub(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    return arithmetic(bays, i64::checked_sub, |a, b| a - b, false);
+}
+
+fn mult(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    return arithmetic(bays, i64::checked_mul, |a, b| a * b, false);
+}
+
+fn div(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    return arithmetic(bays, i64::checked_div, |a, b| a / b, true);
+}
+
+fn modulo(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    return arithmetic(bays, i64::checked_rem, |a, b| a % b, true);
+}
+
+fn step(
+    bays: &[Option<Pallet>],
+    op: fn(i64, i64) -> Option<i64>,
+    float_op: fn(f64, f64) -> f64,
+) -> Result<Pallet, String> {
+    match &bays[0] {
+        Some(Pallet::Int(a)) => integer_arithmetic(op, false, *a, 1),
+        Some(Pallet::Float(a)) => Ok(Pallet::Float(float_op(*a, 1.0))),
+        _ => Err(format!(
+            "Expected one numerical pallet, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn inc(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    return step(bays, i64::checked_add, |a, b| a + b);
+}
+
+fn dec(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    return step(bays, i64::checked_sub, |a, b| a - b);
+}
+
+fn eq(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    return Ok(Pallet::Bool(bays[0] == bays[1]));
+}
+
+fn ne(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    return Ok(Pallet::Bool(bays[0] != bays[1]));
+}
+
+fn compare(
+    bays: &[Option<Pallet>],
+    test: fn(std::cmp::Ordering) -> bool,
+) -> Result<Pallet, String> {
+    let ordering = match (&bays[0], &bays[1]) {
+        (Some(Pallet::Int(a)), Some(Pallet::Int(b))) => a.partial_cmp(b),
+        (Some(Pallet::Float(a)), Some(Pallet::Float(b))) => a.partial_cmp(b),
+        (Some(Pallet::Bool(a)), Some(Pallet::Bool(b))) => a.partial_cmp(b),
+        _ => {
+            return Err(format!(
+                "Expected numerical or boolean pallets, received: {}\n",
+                list_pallets(bays)
+            ))
+        }
+    };
+    return Ok(Pallet::Bool(ordering.is_some_and(test)));
+}
+
+fn gt(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    return compare(bays, |o| o.is_gt());
+}
+
+fn lt(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    return compare(bays, |o| o.is_lt());
+}
+
+fn gte(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    return compare(bays, |o| o.is_ge());
+}
+
+fn lte(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    return compare(bays, |o| o.is_le());
+}
+
+fn and(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match (&bays[0], &bays[1]) {
+        (Some(Pallet::Bool(a)), Some(Pallet::Bool(b))) => Ok(Pallet::Bool(*a && *b)),
+        _ => Err(format!(
+            "Expected two boolean pallets, received {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn or(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match (&bays[0], &bays[1]) {
+        (Some(Pallet::Bool(a)), Some(Pallet::Bool(b))) => Ok(Pallet::Bool(*a || *b)),
+        _ => Err(format!(
+            "Expected two boolean pallets, received {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn not(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match &bays[0] {
+        Some(Pallet::Bool(a)) => Ok(Pallet::Bool(!a)),
+        _ => Err(format!(
+            "Expected two boolean pallets, received {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+/// Small pseudorandom number generator (SplitMix64), the same as the
+/// interpreter's. Every random station keeps its own generator in its state,
+/// seeded from `SEED` and the station's location
+struct Rng(u64);
+impl Rng {
+    fn of(state: &Option<Pallet>, line: usize, col: usize) -> Self {
+        if let Some(Pallet::Int(state)) = state {
+            return Self(*state as u64);
+        }
+        let loc = ((line as u64) << 32) | col as u64;
+        return Self(SEED ^ loc.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    }
+
+    fn save(self, state: &mut Option<Pallet>) {
+        *state = Some(Pallet::Int(self.0 as i64));
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        return z ^ (z >> 31);
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        return (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+    }
+
+    fn below(&mut self, n: u128) -> u128 {
+        return (self.next_u64() as u128 * n) >> 64;
+    }
+}
+
+fn rand(
+    bays: &[Option<Pallet>],
+    state: &mut Option<Pallet>,
+    line: usize,
+    col: usize,
+) -> Result<Output, String> {
+    let mut rng = Rng::of(state, line, col);
+    let value = rng.next_f64();
+    rng.save(state);
+    return Ok(Output::Emit(Pallet::Float(value)));
+}
+
+fn randint(
+    bays: &[Option<Pallet>],
+    state: &mut Option<Pallet>,
+    line: usize,
+    col: usize,
+) -> Result<Output, String> {
+    let (low, high) = match (&bays[0], &bays[1]) {
+        (Some(Pallet::Int(a)), Some(Pallet::Int(b))) => (*a.min(b), *a.max(b)),
+        _ => {
+            return Err(format!(
+                "Expected integer pallets, received: {}\n",
+                list_pallets(bays)
+            ))
+        }
+    };
+    let mut rng = Rng::of(state, line, col);
+    let width = (high as i128 - low as i128 + 1) as u128;
+    let value = (low as i128 + rng.below(width) as i128) as i64;
+    rng.save(state);
+    return Ok(Output::Emit(Pallet::Int(value)));
+}
+
+fn shuffle(
+    bays: &[Option<Pallet>],
+    state: &mut Option<Pallet>,
+    line: usize,
+    col: usize,
+) -> Result<Output, String> {
+    let mut chars: Vec<char> = match &bays[0] {
+        Some(Pallet::String(s)) => s.chars().collect(),
+        _ => {
+            return Err(format!(
+                "Expected string pallet, received: {}\n",
+                list_pallets(bays)
+            ))
+        }
+    };
+    let mut rng = Rng::of(state, line, col);
+    for i in (1..chars.len()).rev() {
+        let j = rng.below(i as u128 + 1) as usize;
+        chars.swap(i, j);
+    }
+    rng.save(state);
+    return Ok(Output::Emit(Pallet::String(chars.into_iter().collect())));
+}
+
+fn sleep(bays: &[Option<Pallet>], io: &mut Streams) -> Result<Output, String> {
+    match &bays[0] {
+        Some(Pallet::Int(ms)) if *ms >= 0 => {
+            let _ = io.stdout.flush();
+            std::thread::sleep(Duration::from_millis(*ms as u64));
+            return Ok(Output::Emit(Pallet::Int(*ms)));
+        }
+        Some(Pallet::Int(ms)) => Err(format!("Can't sleep for a negative duration ({ms}ms)")),
+        _ => Err(format!(
+            "Expected integer pallet, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn clock(io: &mut Streams) -> Result<Output, String> {
+    let ms = io.start.elapsed().as_millis();
+    return Ok(Output::Emit(Pallet::Int(ms as i64)));
+}
+
+fn now() -> Result<Output, String> {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    return Ok(Output::Emit(Pallet::Int(secs as i64)));
+}
+
+fn list_index(i: i64, len: usize) -> Result<usize, String> {
+    if i < 0 || i as usize >= len {
+        return Err(format!(
+            "Index {i} is out of range for a list of length {len}"
+        ));
+    }
+    return Ok(i as usize);
+}
+
+/// Splits the bays into a pallet of a type and the rest of the pallets in bay
+/// order, the pallet in the first bay if more than one is of the type
+fn split_pallets(
+    bays: &[Option<Pallet>],
+    is_type: fn(&Pallet) -> bool,
+) -> Option<(&Pallet, Vec<&Pallet>)> {
+    let matching = bays
+        .iter()
+        .filter(|p| p.as_ref().is_some_and(is_type))
+        .count();
+    let i = if matching > 1 {
+        0
+    } else {
+        bays.iter().position(|p| p.as_ref().is_some_and(is_type))?
+    };
+    if !bays[i].as_ref().is_some_and(is_type) {
+        return None;
+    }
+    let rest = bays
+        .iter()
+        .enumerate()
+        .filter(|(j, _)| *j != i)
+        .filter_map(|(_, p)| p.as_ref())
+        .collect();
+    return Some((bays[i].as_ref()?, rest));
+}
+
+fn split_list(bays: &[Option<Pallet>]) -> Option<(&Vec<Pallet>, Vec<&Pallet>)> {
+    match split_pallets(bays, |p| matches!(p, Pallet::List(_)))? {
+        (Pallet::List(list), rest) => Some((list, rest)),
+        _ => None,
+    }
+}
+
+fn compare_pallets(a: &Pallet, b: &Pallet) -> Option<std::cmp::Ordering> {
+    match (a, b) {
+        (Pallet::Int(a), Pallet::Int(b)) => Some(a.cmp(b)),
+        (Pallet::Float(a), Pallet::Float(b)) => Some(a.total_cmp(b)),
+        (Pallet::String(a), Pallet::String(b)) => Some(a.cmp(b)),
+        (Pallet::Char(a), Pallet::Char(b)) => Some(a.cmp(b)),
+        (Pallet::Bool(a), Pallet::Bool(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+fn push(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match split_list(bays).as_ref().map(|(l, rest)| (l, rest.as_slice())) {
+        Some((list, [p])) => {
+            let mut list = list.to_vec();
+            list.push((*p).clone());
+            Ok(Pallet::List(list))
+        }
+        _ => Err(format!(
+            "Expected list and pallet, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn pop(bays: &[Option<Pallet>]) -> Result<Output, String> {
+    match &bays[0] {
+        Some(Pallet::List(list)) => {
+            let mut list = list.clone();
+            let Some(item) = list.pop() else {
+                return Err(String::from("Can't pop from an empty list"));
+            };
+            Ok(Output::EmitTo(vec![
+                (Direction::East, item),
+                (Direction::West, Pallet::List(list)),
+            ]))
+        }
+        _ => Err(format!(
+            "Expected list pallet, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn get(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match split_list(bays).as_ref().map(|(l, rest)| (l, rest.as_slice())) {
+        Some((list, [Pallet::Int(i)])) => Ok(list[list_index(*i, list.len())?].clone()),
+        _ => Err(format!(
+            "Expected list and integer pallets, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn set(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match split_list(bays).as_ref().map(|(l, rest)| (l, rest.as_slice())) {
+        Some((list, [Pallet::Int(i), p])) => {
+            let mut list = list.to_vec();
+            let i = list_index(*i, list.len())?;
+            list[i] = (*p).clone();
+            Ok(Pallet::List(list))
+        }
+        _ => Err(format!(
+            "Expected list, integer and pallet, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn len(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match &bays[0] {
+        Some(Pallet::List(list)) => Ok(Pallet::Int(list.len() as i64)),
+        Some(Pallet::String(s)) => Ok(Pallet::Int(s.chars().count() as i64)),
+        _ => Err(format!(
+            "Expected list or string pallet, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn concat(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match (&bays[0], &bays[1]) {
+        (Some(Pallet::List(a)), Some(Pallet::List(b))) => {
+            Ok(Pallet::List([a.as_slice(), b].concat()))
+        }
+        _ => Err(format!(
+            "Expected list pallets, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn slice(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match split_list(bays).as_ref().map(|(l, rest)| (l, rest.as_slice())) {
+        Some((list, [Pallet::Int(start), Pallet::Int(end)])) => {
+            if *start < 0 || start > end || *end as usize > list.len() {
+                return Err(format!(
+                    "Slice {start}..{end} is out of range for a list of length {}",
+                    list.len()
+                ));
+            }
+            Ok(Pallet::List(list[*start as usize..*end as usize].to_vec()))
+        }
+        _ => Err(format!(
+            "Expected list and two integer pallets, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn sort(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    let list = match &bays[0] {
+        Some(Pallet::List(list)) => list,
+        _ => {
+            return Err(format!(
+                "Expected list pallet, received: {}\n",
+                list_pallets(bays)
+            ))
+        }
+    };
+    if let Some(first) = list.first() {
+        if list.iter().any(|p| compare_pallets(first, p).is_none()) {
+            return Err(format!(
+                "Expected a list of comparable pallets of one type, received: {}",
+                bays[0].as_ref().unwrap()
+            ));
+        }
+    }
+    let mut list = list.clone();
+    list.sort_by(|a, b| compare_pallets(a, b).unwrap());
+    return Ok(Pallet::List(list));
+}
+
+fn is_key(pallet: &Pallet) -> bool {
+    return matches!(
+        pallet,
+        Pallet::Bool(_) | Pallet::Int(_) | Pallet::Char(_) | Pallet::String(_)
+    );
+}
+
+/// Orders keys by type (booleans, integers, characters, then strings) and then
+/// by value, the same as the interpreter's maps
+fn compare_keys(a: &Pallet, b: &Pallet) -> std::cmp::Ordering {
+    fn rank(key: &Pallet) -> u8 {
+        return match key {
+            Pallet::Bool(_) => 0,
+            Pallet::Char(_) => 2,
+            Pallet::String(_) => 3,
+            _ => 1,
+        };
+    }
+    return match (a, b) {
+        (Pallet::Bool(a), Pallet::Bool(b)) => a.cmp(b),
+        (Pallet::Int(a), Pallet::Int(b)) => a.cmp(b),
+        (Pallet::Char(a), Pallet::Char(b)) => a.cmp(b),
+        (Pallet::String(a), Pallet::String(b)) => a.cmp(b),
+        (a, b) => rank(a).cmp(&rank(b)),
+    };
+}
+
+fn key_error(key: &Pallet) -> String {
+    return format!("Map keys must be strings, integers, characters or booleans, received {key}");
+}
+
+fn split_map(bays: &[Option<Pallet>]) -> Option<(&Vec<(Pallet, Pallet)>, Vec<&Pallet>)> {
+    match split_pallets(bays, |p| matches!(p, Pallet::Map(_)))? {
+        (Pallet::Map(entries), rest) => Some((entries, rest)),
+        _ => None,
+    }
+}
+
+fn insert(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match split_map(bays).as_ref().map(|(m, rest)| (m, rest.as_slice())) {
+        Some((_, [key, _])) if !is_key(key) => Err(key_error(key)),
+        Some((entries, [key, value])) => {
+            let mut entries = entries.to_vec();
+            match entries.binary_search_by(|(k, _)| compare_keys(k, key)) {
+                Ok(i) => entries[i].1 = (*value).clone(),
+                Err(i) => entries.insert(i, ((*key).clone(), (*value).clone())),
+            }
+            Ok(Pallet::Map(entries))
+        }
+        _ => Err(format!(
+            "Expected map, key and value, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn lookup(bays: &[Option<Pallet>]) -> Result<Output, String> {
+    match split_map(bays).as_ref().map(|(m, rest)| (m, rest.as_slice())) {
+        Some((entries, [key])) => {
+            match entries.binary_search_by(|(k, _)| compare_keys(k, key)) {
+                Ok(i) => Ok(Output::EmitTo(vec![(Direction::East, entries[i].1.clone())])),
+                Err(_) if is_key(key) => Ok(Output::EmitTo(vec![(Direction::West, (*key).clone())])),
+                Err(_) => Err(key_error(key)),
+            }
+        }
+        _ => Err(format!(
+            "Expected map and key, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn remove(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match split_map(bays).as_ref().map(|(m, rest)| (m, rest.as_slice())) {
+        Some((entries, [key])) if is_key(key) => {
+            let mut entries = entries.to_vec();
+            if let Ok(i) = entries.binary_search_by(|(k, _)| compare_keys(k, key)) {
+                entries.remove(i);
+            }
+            Ok(Pallet::Map(entries))
+        }
+        Some((_, [key])) => Err(key_error(key)),
+        _ => Err(format!(
+            "Expected map and key, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn keys(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match &bays[0] {
+        Some(Pallet::Map(entries)) => Ok(Pallet::List(
+            entries.iter().map(|(key, _)| key.clone()).collect(),
+        )),
+        _ => Err(format!(
+            "Expected map pallet, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn size(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match &bays[0] {
+        Some(Pallet::Map(entries)) => Ok(Pallet::Int(entries.len() as i64)),
+        _ => Err(format!(
+            "Expected map pallet, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn convert_error(p: &Pallet, type_name: &str) -> String {
+    return format!("Can't convert {p} to {type_name}");
+}
+
+fn input(bays: &[Option<Pallet>]) -> Result<&Pallet, String> {
+    match &bays[0] {
+        Some(p) => Ok(p),
+        None => Err(format!(
+            "Expected a pallet, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn to_int(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    let p = input(bays)?;
+    let result = match p {
+        Pallet::Int(i) => Some(*i),
+        Pallet::Float(f) if f.is_finite() => {
+            let f = f.trunc();
+            (f >= i64::MIN as f64 && f < i64::MAX as f64).then_some(f as i64)
+        }
+        Pallet::String(s) => s.trim().parse::<i64>().ok(),
+        Pallet::Char(c) => Some(*c as i64),
+        Pallet::Bool(b) => Some(*b as i64),
+        _ => None,
+    };
+    return result
+        .map(Pallet::Int)
+        .ok_or_else(|| convert_error(p, "an integer"));
+}
+
+fn to_float(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    let p = input(bays)?;
+    let result = match p {
+        Pallet::Int(i) => Some(*i as f64),
+        Pallet::Float(f) => Some(*f),
+        Pallet::String(s) => s.trim().parse::<f64>().ok(),
+        Pallet::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+        _ => None,
+    };
+    return result
+        .map(Pallet::Float)
+        .ok_or_else(|| convert_error(p, "a float"));
+}
+
+fn to_str(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    return Ok(Pallet::String(input(bays)?.text()));
+}
+
+fn to_char(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    let p = input(bays)?;
+    let result = match p {
+        Pallet::Int(i) => u32::try_from(*i).ok().and_then(char::from_u32),
+        Pallet::Char(c) => Some(*c),
+        Pallet::String(s) => {
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(c),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    return result
+        .map(Pallet::Char)
+        .ok_or_else(|| convert_error(p, "a character"));
+}
+
+fn to_bool(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    let p = input(bays)?;
+    let result = match p {
+        Pallet::Bool(b) => Some(*b),
+        Pallet::String(s) => match s.trim() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        },
+        Pallet::Int(i) => Some(*i != 0),
+        Pallet::Float(f) => Some(*f != 0.0),
+        _ => None,
+    };
+    return result
+        .map(Pallet::Bool)
+        .ok_or_else(|| convert_error(p, "a boolean"));
+}
+
+fn type_of(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    return Ok(Pallet::String(String::from(input(bays)?.type_name())));
+}
+
+fn split_string(bays: &[Option<Pallet>]) -> Option<(&String, Vec<&Pallet>)> {
+    match split_pallets(bays, |p| matches!(p, Pallet::String(_)))? {
+        (Pallet::String(s), rest) => Some((s, rest)),
+        _ => None,
+    }
+}
+
+/// The text of a string or character pallet, used to search strings
+fn pattern(p: Option<&Pallet>) -> Option<String> {
+    match p {
+        Some(Pallet::String(s)) => Some(s.clone()),
+        Some(Pallet::Char(c)) => Some(c.to_string()),
+        _ => None,
+    }
+}
+
+fn charat(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match split_string(bays).as_ref().map(|(s, rest)| (s, rest.as_slice())) {
+        Some((s, [Pallet::Int(i)])) => {
+            let len = s.chars().count();
+            if *i < 0 || *i as usize >= len {
+                return Err(format!(
+                    "Index {i} is out of range for a string of length {len}"
+                ));
+            }
+            Ok(Pallet::Char(s.chars().nth(*i as usize).unwrap()))
+        }
+        _ => Err(format!(
+            "Expected string and integer pallets, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn substr(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match split_string(bays).as_ref().map(|(s, rest)| (s, rest.as_slice())) {
+        Some((s, [Pallet::Int(start), Pallet::Int(end)])) => {
+            let len = s.chars().count();
+            if *start < 0 || start > end || *end as usize > len {
+                return Err(format!(
+                    "Substring {start}..{end} is out of range for a string of length {len}"
+                ));
+            }
+            Ok(Pallet::String(
+                s.chars()
+                    .skip(*start as usize)
+                    .take((end - start) as usize)
+                    .collect(),
+            ))
+        }
+        _ => Err(format!(
+            "Expected string and two integer pallets, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn split(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match (&bays[0], pattern(bays[1].as_ref())) {
+        (Some(Pallet::String(s)), Some(separator)) if separator.is_empty() => Ok(Pallet::List(
+            s.chars().map(|c| Pallet::String(c.to_string())).collect(),
+        )),
+        (Some(Pallet::String(s)), Some(separator)) => Ok(Pallet::List(
+            s.split(separator.as_str())
+                .map(|part| Pallet::String(part.to_owned()))
+                .collect(),
+        )),
+        _ => Err(format!(
+            "Expected string and separator pallets, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn join(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    if let Some((Pallet::List(list), rest)) = split_pallets(bays, |p| matches!(p, Pallet::List(_))) {
+        if let Some(separator) = pattern(rest.first().copied()) {
+            let items: Vec<String> = list.iter().map(|item| item.text()).collect();
+            return Ok(Pallet::String(items.join(&separator)));
+        }
+    }
+    return Err(format!(
+        "Expected list and separator pallets, received: {}\n",
+        list_pallets(bays)
+    ));
+}
+
+fn map_string(bays: &[Option<Pallet>], f: fn(&str) -> String) -> Result<Pallet, String> {
+    match &bays[0] {
+        Some(Pallet::String(s)) => Ok(Pallet::String(f(s))),
+        _ => Err(format!(
+            "Expected string pallet, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn upper(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    return map_string(bays, str::to_uppercase);
+}
+
+fn lower(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    return map_string(bays, str::to_lowercase);
+}
+
+fn trim(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    return map_string(bays, |s| s.trim().to_owned());
+}
+
+fn contains(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match (&bays[0], pattern(bays[1].as_ref())) {
+        (Some(Pallet::String(s)), Some(pattern)) => Ok(Pallet::Bool(s.contains(pattern.as_str()))),
+        _ => Err(format!(
+            "Expected string and pattern pallets, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn startswith(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match (&bays[0], pattern(bays[1].as_ref())) {
+        (Some(Pallet::String(s)), Some(prefix)) => Ok(Pallet::Bool(s.starts_with(prefix.as_str()))),
+        _ => Err(format!(
+            "Expected string and prefix pallets, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn replace(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match (&bays[0], pattern(bays[1].as_ref()), pattern(bays[2].as_ref())) {
+        (Some(Pallet::String(_)), Some(from), Some(_)) if from.is_empty() => {
+            Err(String::from("Can't replace an empty pattern"))
+        }
+        (Some(Pallet::String(s)), Some(from), Some(to)) => {
+            Ok(Pallet::String(s.replace(from.as_str(), &to)))
+        }
+        _ => Err(format!(
+            "Expected string, pattern and replacement pallets, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
+
+fn find(bays: &[Option<Pallet>]) -> Result<Pallet, String> {
+    match (&bays[0], pattern(bays[1].as_ref())) {
+        (Some(Pallet::String(s)), Some(pattern)) => Ok(Pallet::Int(match s.find(pattern.as_str()) {
+            Some(byte) => s[..byte].chars().count() as i64,
+            None => -1,
+        })),
+        _ => Err(format!(
+            "Expected string and pattern pallets, received: {}\n",
+            list_pallets(bays)
+        )),
+    }
+}
@@ -42,6 +42,16 @@ fn test_hello_world_fancy() {
     cmd.assert().success().stdout("hello world\n");
 }
 
+#[test]
+fn test_multiple_start_stations() {
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str("[start]═─{\"hello world\"}═─[println]\n[start]")
+        .unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path());
+    cmd.assert().failure();
+}
+
 #[test]
 fn test_hello_world_reverse() {
     let file = NamedTempFile::new("tmp").unwrap();
@@ -122,3 +132,802 @@ fn test_greeting() {
         .success()
         .stdout("What is your name? Hello Jaxson!\n");
 }
+
+#[test]
+fn test_count() {
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str(
+        "
+[start]═─[]═─[]═─[count]═─[println]
+   ╚──────────────┘",
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path());
+    cmd.assert().success().stdout("1\n2\n");
+}
+
+#[test]
+fn test_accumulate() {
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str(
+        "
+[start]═─{5}═─[acc]═─[println]
+   ╚─{3}═─[]═──┘",
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path());
+    cmd.assert().success().stdout("5\n8\n");
+}
+
+#[test]
+fn test_latch() {
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str(
+        "
+[start]═─{42}═─[latch:W]═─[println]
+   ╚─[]═─[]═─[]═┘",
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path());
+    cmd.assert().success().stdout("42\n");
+}
+
+#[test]
+fn test_branch() {
+    for (condition, expected) in [("true", "east\n"), ("false", "west\n")] {
+        let file = NamedTempFile::new("tmp").unwrap();
+        let padding = "─".repeat(condition.len());
+        file.write_str(
+            format!(
+                "
+[println]─═{{\"west\"}}─═[branch]═─{{\"east\"}}═─[println]
+                      │   └─═{{{condition}}}─═[start]
+                      └────────────{padding}╝"
+            )
+            .as_str(),
+        )
+        .unwrap();
+        let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+        cmd.arg(file.path());
+        cmd.assert().success().stdout(expected);
+    }
+}
+
+#[test]
+#[cfg(not(feature = "bigint"))]
+fn test_int_overflow() {
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str(
+        "
+[start]═─{9_223_372_036_854_775_807}═─[+]═─[println]
+   ╚─{1}═──────────────────────────────┘",
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path());
+    cmd.assert().failure();
+}
+
+#[test]
+#[cfg(feature = "bigint")]
+fn test_bigint_promotion() {
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str(
+        "
+[start]═─{9_223_372_036_854_775_807}═─[+]═─[]═─[*]═─[println]
+   ╚─{1}═──────────────────────────────┘   ╚───┘",
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout("85070591730234615865843651857942052864\n");
+}
+
+#[test]
+fn test_trace() {
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str("[start]═─{\"hi\"}═─[println]").unwrap();
+    let trace = NamedTempFile::new("trace.jsonl").unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path()).arg("--trace").arg(trace.path());
+    cmd.assert().success().stdout("hi\n");
+    let contents = std::fs::read_to_string(trace.path()).unwrap();
+    let events: Vec<&str> = contents
+        .lines()
+        .map(|l| {
+            l.split("\"event\":\"")
+                .nth(1)
+                .unwrap()
+                .split('"')
+                .next()
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(
+        events,
+        vec![
+            "produced",
+            "step_start",
+            "moved",
+            "triggered",
+            "produced",
+            "step_end",
+            "step_start",
+            "moved",
+            "triggered",
+            "dropped",
+            "step_end"
+        ]
+    );
+}
+
+#[test]
+fn test_run_subcommand() {
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str("[start]═─{\"hi\"}═─[println]").unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg("run").arg(file.path());
+    cmd.assert().success().stdout("hi\n");
+}
+
+#[test]
+fn test_animate() {
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str("[start]═─{\"hi\"}═─[println]").unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg("run")
+        .arg(file.path())
+        .args(["--animate", "--speed", "0", "--no-color"]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("[start]●─{\"hi\"}═─[println]"))
+        .stdout(predicates::str::ends_with("output ────────\r\nhi\r\n"));
+}
+
+#[test]
+fn test_debug() {
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str("[start]═─{\"hi\"}═─[println]").unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg("debug").arg(file.path());
+    cmd.write_stdin("break println\ncontinue\nstep\nquit\n");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("Breakpoint on #2 [println]"))
+        .stdout(predicates::str::contains(
+            "hi\nProgram finished after 2 steps",
+        ));
+}
+
+#[test]
+fn test_profile() {
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str("[start]═─{\"hi\"}═─[println]").unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path())
+        .args(["--profile", "--profile-sort", "index", "--no-color"]);
+    cmd.assert().success().stdout(predicates::str::contains(
+        "│ #0 [start] 0, #1 [assign] 1, #2 [println] 1",
+    ));
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path())
+        .args(["--profile", "--profile-sort", "heat"]);
+    cmd.assert().failure();
+}
+
+#[test]
+fn test_coverage() {
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str(
+        "
+[start]═─{false}═─[gate]═─[println]
+   ╚─{\"x\"}═─────────┘",
+    )
+    .unwrap();
+    let coverage = NamedTempFile::new("coverage.json").unwrap();
+    for _ in 0..2 {
+        let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+        cmd.arg(file.path()).arg("--coverage").arg(coverage.path());
+        cmd.assert().success();
+    }
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg("coverage")
+        .arg(file.path())
+        .arg(coverage.path())
+        .arg("--no-color");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::starts_with("runs: 2\nstations fired: 4/5"))
+        .stdout(predicates::str::contains("never fired: #3 [println]"));
+
+    let html = NamedTempFile::new("coverage.html").unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg("coverage")
+        .arg(file.path())
+        .arg(coverage.path())
+        .arg("--html")
+        .arg(html.path());
+    cmd.assert().success();
+    assert!(std::fs::read_to_string(html.path())
+        .unwrap()
+        .contains("<span class=\"unused\">═─[println]</span>"));
+}
+
+#[test]
+fn test_physical_belts() {
+    // with physical belts, the pallet on the shorter belt arrives first
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str(
+        "
+[start]═─{\"long\"}═──────[print]
+   ╚─{\"short\"}═─[print]",
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path());
+    cmd.assert().success().stdout("longshort");
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path()).arg("--physical-belts");
+    cmd.assert().success().stdout("shortlong");
+}
+
+#[test]
+fn test_parallel() {
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str("[start]═─{1}═─[++]═─[println]\n   ╚─{5}═─[++]═─[println]")
+        .unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path()).arg("--parallel");
+    cmd.assert().success().stdout("2\n6\n");
+}
+
+#[test]
+fn test_dump_ir() {
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str("[start]═─{\"hi\"}═─[println]").unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path()).arg("--dump-ir");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::starts_with(
+            "; 3 instructions, 2 slots, 2 fan-out entries\n",
+        ))
+        .stdout(predicates::str::contains("call [println]"));
+}
+
+#[test]
+fn test_optimize_keeps_steps() {
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str("[start]═─{\"hi\"}═─[]═─[]═──[]═─[println]")
+        .unwrap();
+    for args in [vec!["-b"], vec!["-b", "--no-optimize"]] {
+        let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+        cmd.arg(file.path()).args(args);
+        cmd.assert()
+            .success()
+            .stdout(predicates::str::starts_with("hi\n"))
+            .stdout(predicates::str::contains(" steps      5\n"));
+    }
+}
+
+/// Transpiles a program with `factory build` and compiles it with rustc in a
+/// directory, returns the path of the executable
+fn build(file: &std::path::Path, args: &[&str], dir: &assert_fs::TempDir) -> std::path::PathBuf {
+    let rust_file = dir.child("main.rs");
+    let exe = dir.child("main");
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg("build")
+        .arg(file)
+        .args(args)
+        .arg("-o")
+        .arg(rust_file.path());
+    cmd.assert().success();
+    let rustc = std::process::Command::new("rustc")
+        .arg(rust_file.path())
+        .arg("-o")
+        .arg(exe.path())
+        .output()
+        .unwrap();
+    assert!(
+        rustc.status.success(),
+        "{}",
+        String::from_utf8_lossy(&rustc.stderr)
+    );
+    return exe.to_path_buf();
+}
+
+/// Builds a program and checks it prints the same output as the interpreter
+/// given the same input. The arguments are passed to both
+fn assert_builds_like_interpreter(file: &std::path::Path, args: &[&str], input: &str) {
+    let dir = assert_fs::TempDir::new().unwrap();
+    let exe = build(file, args, &dir);
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file).args(args).write_stdin(input);
+    let expected = cmd.assert().success().get_output().stdout.clone();
+    Command::new(exe)
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(expected);
+}
+
+#[test]
+fn test_build() {
+    // transpiled programs compile with rustc and behave like the interpreter
+    for (name, input) in [
+        ("hello_world", ""),
+        ("fizzbuzz", ""),
+        ("fibonacci", ""),
+        ("greet", "Jaxson\n"),
+    ] {
+        let example = format!("../examples/{name}.factory");
+        assert_builds_like_interpreter(std::path::Path::new(&example), &[], input);
+    }
+}
+
+#[test]
+fn test_snapshot_resume() {
+    let file = NamedTempFile::new("fibonacci.factory").unwrap();
+    file.write_str(include_str!("../../examples/fibonacci.factory"))
+        .unwrap();
+    let snapshot = NamedTempFile::new("snapshot.json").unwrap();
+
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path());
+    let expected = cmd.assert().success().get_output().stdout.clone();
+
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path())
+        .arg("--snapshot")
+        .arg(snapshot.path())
+        .arg("--snapshot-at")
+        .arg("30");
+    let mut output = cmd
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("at step 30"))
+        .get_output()
+        .stdout
+        .clone();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg("resume").arg(snapshot.path());
+    output.extend(cmd.assert().success().get_output().stdout.clone());
+    assert_eq!(output, expected);
+
+    // the program can't be resumed once its source has changed
+    file.write_str(&include_str!("../../examples/fibonacci.factory").replace("{92}", "{90}"))
+        .unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg("resume").arg(snapshot.path());
+    cmd.assert()
+        .failure()
+        .stdout(predicates::str::contains("has changed since the snapshot"));
+}
+
+#[test]
+fn test_snapshot_resume_seed() {
+    // the random station only fires after the snapshot is taken
+    let file = NamedTempFile::new("rand.factory").unwrap();
+    file.write_str("[start]═─[]═─[]═─[]═─[rand]═─[println]")
+        .unwrap();
+    let snapshot = NamedTempFile::new("snapshot.json").unwrap();
+
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path()).args(["--seed", "7"]);
+    let expected = cmd.assert().success().get_output().stdout.clone();
+
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path())
+        .args(["--seed", "7", "--snapshot"])
+        .arg(snapshot.path())
+        .args(["--snapshot-at", "2"]);
+    cmd.assert().success().stdout("");
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg("resume").arg(snapshot.path());
+    cmd.assert().success().stdout(expected);
+}
+
+#[test]
+fn test_record_replay() {
+    let session = NamedTempFile::new("session.log").unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg("../examples/greet.factory")
+        .arg("--record")
+        .arg(session.path())
+        .write_stdin("Jaxson\n");
+    cmd.assert()
+        .success()
+        .stdout("What is your name? Hello Jaxson!\n");
+    session.assert(predicates::str::contains("\"line\":\"Jaxson\""));
+
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg("../examples/greet.factory")
+        .arg("--replay")
+        .arg(session.path())
+        .write_stdin("Sam\n");
+    cmd.assert()
+        .success()
+        .stdout("What is your name? Hello Jaxson!\n");
+}
+
+const RANDOM_SRC: &str = "
+[start]═─{\"hello\"}═─[shuffle]═─[println]
+ ║ ║╚─{6}═─[randint]═─[println]
+ │ │          │
+ │ └─{1}═─────┘
+ └─[rand]═─[println]";
+
+#[test]
+fn test_random() {
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str(RANDOM_SRC).unwrap();
+    let mut outputs = Vec::new();
+    for seed in ["3", "3", "4"] {
+        let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+        cmd.arg(file.path()).arg("--seed").arg(seed);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        let float: f64 = lines[0].parse().unwrap();
+        assert!((0.0..1.0).contains(&float));
+        let mut chars: Vec<char> = lines[1].chars().collect();
+        chars.sort();
+        assert_eq!(chars, ['e', 'h', 'l', 'l', 'o']);
+        assert!((1..=6).contains(&lines[2].parse::<i64>().unwrap()));
+        outputs.push(output);
+    }
+    assert_eq!(outputs[0], outputs[1]);
+    assert_ne!(outputs[0], outputs[2]);
+
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path()).arg("--seed").arg("3").arg("-d");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("Seed:\t\t3\n"));
+}
+
+#[test]
+fn test_build_random() {
+    // built programs produce the same numbers as the interpreter with the same seed
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str(RANDOM_SRC).unwrap();
+    assert_builds_like_interpreter(file.path(), &["--seed", "3"], "");
+    assert_builds_like_interpreter(file.path(), &["--seed", "3", "--no-optimize"], "");
+}
+
+#[test]
+fn test_time() {
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str("[start]═─{50}═─[sleep]═─[]═─[clock]═─[println]")
+        .unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let ms: u64 = String::from_utf8(output).unwrap().trim().parse().unwrap();
+    assert!((50..1000).contains(&ms), "{ms}");
+
+    // a resumed program's clock carries on from when it was stopped
+    let snapshot = NamedTempFile::new("snapshot.json").unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path())
+        .arg("--snapshot")
+        .arg(snapshot.path())
+        .args(["--snapshot-at", "2"]);
+    cmd.assert().success().stdout("");
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg("resume").arg(snapshot.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let ms: u64 = String::from_utf8(output).unwrap().trim().parse().unwrap();
+    assert!(ms >= 50, "{ms}");
+
+    file.write_str("[start]═─[now]═─[println]").unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let secs: u64 = String::from_utf8(output).unwrap().trim().parse().unwrap();
+    let expected = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    assert!(secs.abs_diff(expected) <= 5);
+}
+
+#[test]
+fn test_build_time() {
+    let dir = assert_fs::TempDir::new().unwrap();
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str("[start]═─{50}═─[sleep]═─[]═─[clock]═─[println]")
+        .unwrap();
+    let output = Command::new(build(file.path(), &[], &dir))
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let ms: u64 = String::from_utf8(output).unwrap().trim().parse().unwrap();
+    assert!((50..1000).contains(&ms), "{ms}");
+
+    file.write_str("[start]═─[now]═─[println]").unwrap();
+    let output = Command::new(build(file.path(), &[], &dir))
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let secs: u64 = String::from_utf8(output).unwrap().trim().parse().unwrap();
+    let expected = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    assert!(secs.abs_diff(expected) <= 5);
+}
+
+/// Programs using lists, and what they print
+fn list_programs() -> Vec<(String, &'static str)> {
+    return vec![
+        (
+            "[start]═─{[3, \"a\", 2.5, [true, 'c']]}═─[println]".to_owned(),
+            "[3, \"a\", 2.5, [true, 'c']]\n",
+        ),
+        (
+            "[start]═─{[3, 1, 2]}═─[sort]═─[println]".to_owned(),
+            "[1, 2, 3]\n",
+        ),
+        (
+            "[start]═─{[1, 2]}═─[push]═─[println]\n   ╚─{3}═──────────┘".to_owned(),
+            "[1, 2, 3]\n",
+        ),
+        (
+            "[start]═─{[1, 2]}═─[push]═─[println]\n   ╚─{[3]}═──────────┘".to_owned(),
+            "[3, [1, 2]]\n",
+        ),
+        (
+            "[start]═─{[3]}═─[push]═─[println]\n   ╚─{[1, 2]}═────┘".to_owned(),
+            "[1, 2, [3]]\n",
+        ),
+        (
+            "[start]═─{[1, 2]}═─[get]═─[println]\n   ╚─{1}═──────────┘".to_owned(),
+            "2\n",
+        ),
+        (
+            format!(
+                "[start]═─{{[1, 2, 3]}}═─[set]═─[println]\n   ║╚─{{\"x\"}}═{}┘│\n   └─{{1}}═{}┘",
+                "─".repeat(10),
+                "─".repeat(14)
+            ),
+            "[1, \"x\", 3]\n",
+        ),
+        (
+            "[println]─═[pop]═─[println]\n            │\n            ║\n[start]═─{[1, 2]}"
+                .to_owned(),
+            "[1]\n2\n",
+        ),
+        (
+            format!(
+                "[start]═─{{[1, 2, 3, 4]}}═─[slice]═─[println]\n   ║╚─{{3}}═{}┘│\n   └─{{1}}═{}┘",
+                "─".repeat(17),
+                "─".repeat(19)
+            ),
+            "[2, 3]\n",
+        ),
+        (
+            "[start]═─{[1, 2]}═─[concat]═─[len]═─[println]\n   ╚─{[3]}═──────────┘".to_owned(),
+            "3\n",
+        ),
+    ];
+}
+
+#[test]
+fn test_lists() {
+    for (src, expected) in list_programs() {
+        let file = NamedTempFile::new("tmp").unwrap();
+        file.write_str(&src).unwrap();
+        let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+        cmd.arg(file.path());
+        cmd.assert().success().stdout(expected);
+    }
+}
+
+#[test]
+fn test_build_lists() {
+    for (src, _) in list_programs() {
+        let file = NamedTempFile::new("tmp").unwrap();
+        file.write_str(&src).unwrap();
+        assert_builds_like_interpreter(file.path(), &["--no-optimize"], "");
+    }
+}
+
+/// Programs using maps, and what they print
+fn map_programs() -> Vec<(String, &'static str)> {
+    let lookup = |key: &str| {
+        format!(
+            "[println]─═[lookup]═─[println]
+            │  │
+            │  ║
+            │ {{{key}}}
+            │   │
+            ║   ║
+[start]═─{{[\"a\": 1]}}"
+        )
+    };
+    return vec![
+        (
+            "[start]═─{[\"b\": 2, \"a\": 1, 3: true]}═─[println]".to_owned(),
+            "[3: true, \"a\": 1, \"b\": 2]\n",
+        ),
+        (
+            format!(
+                "[start]═─{{[\"a\": 1]}}═─[insert]═─[println]\n   ║╚─{{2}}═{}┘│\n   └─{{\"b\"}}═{}┘",
+                "─".repeat(13),
+                "─".repeat(13)
+            ),
+            "[\"a\": 1, \"b\": 2]\n",
+        ),
+        (
+            format!(
+                "[start]═─{{[\"c\": 3]}}═─[insert]═─[println]\n   ║╚─{{\"b\"}}═{}┘│\n   └─{{[\"a\": 1]}}═{}┘",
+                "─".repeat(9),
+                "─".repeat(6)
+            ),
+            "[\"a\": 1, \"b\": [\"c\": 3]]\n",
+        ),
+        (lookup("\"a\""), "1\n"),
+        (lookup("\"c\""), "c\n"),
+        (
+            "[start]═─{[\"a\": 1, \"b\": 2]}═─[remove]═─[println]\n   ╚─{\"a\"}═───────────────────┘"
+                .to_owned(),
+            "[\"b\": 2]\n",
+        ),
+        (
+            "[start]═─{[\"b\": 2, \"a\": 1]}═─[keys]═─[println]".to_owned(),
+            "[\"a\", \"b\"]\n",
+        ),
+        (
+            "[start]═─{[\"b\": 2, \"a\": 1]}═─[size]═─[println]".to_owned(),
+            "2\n",
+        ),
+        (
+            "[start]═─{[\"a\": 1, \"b\": 2]}═─[=]═─[println]\n   ╚─{[\"b\": 2, \"a\": 1]}═──────┘"
+                .to_owned(),
+            "true\n",
+        ),
+    ];
+}
+
+#[test]
+fn test_maps() {
+    for (src, expected) in map_programs() {
+        let file = NamedTempFile::new("tmp").unwrap();
+        file.write_str(&src).unwrap();
+        let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+        cmd.arg(file.path());
+        cmd.assert().success().stdout(expected);
+    }
+}
+
+#[test]
+fn test_build_maps() {
+    for (src, _) in map_programs() {
+        let file = NamedTempFile::new("tmp").unwrap();
+        file.write_str(&src).unwrap();
+        assert_builds_like_interpreter(file.path(), &["--no-optimize"], "");
+    }
+}
+
+/// Conversions of literals that succeed, and what they print
+const CONVERSIONS: [(&str, &str, &str); 13] = [
+    ("\" 42 \"", "int", "42\n"),
+    ("2.9", "int", "2\n"),
+    ("'A'", "int", "65\n"),
+    ("true", "int", "1\n"),
+    ("\"1.5\"", "float", "1.5\n"),
+    ("3", "float", "3\n"),
+    ("[1, 'a']", "str", "[1, 'a']\n"),
+    ("97", "char", "a\n"),
+    ("\"é\"", "char", "é\n"),
+    ("\"false\"", "bool", "false\n"),
+    ("0.5", "bool", "true\n"),
+    ("12", "type", "int\n"),
+    ("[:]", "type", "map\n"),
+];
+
+/// Conversions of literals that fail
+const FAILED_CONVERSIONS: [(&str, &str); 4] = [
+    ("\"abc\"", "int"),
+    ("\"ab\"", "char"),
+    ("55296", "char"),
+    ("\"yes\"", "bool"),
+];
+
+#[test]
+fn test_conversions() {
+    for (literal, station, expected) in CONVERSIONS {
+        let file = NamedTempFile::new("tmp").unwrap();
+        file.write_str(&format!("[start]═─{{{literal}}}═─[{station}]═─[println]"))
+            .unwrap();
+        let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+        cmd.arg(file.path());
+        cmd.assert().success().stdout(expected);
+    }
+    for (literal, station) in FAILED_CONVERSIONS {
+        let file = NamedTempFile::new("tmp").unwrap();
+        file.write_str(&format!("[start]═─{{{literal}}}═─[{station}]═─[println]"))
+            .unwrap();
+        let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+        cmd.arg(file.path());
+        cmd.assert()
+            .failure()
+            .stdout(predicates::str::contains("Can't convert"));
+    }
+}
+
+#[test]
+fn test_build_conversions() {
+    let dir = assert_fs::TempDir::new().unwrap();
+    for (literal, station, _) in CONVERSIONS {
+        let file = NamedTempFile::new("tmp").unwrap();
+        file.write_str(&format!("[start]═─{{{literal}}}═─[{station}]═─[println]"))
+            .unwrap();
+        assert_builds_like_interpreter(file.path(), &["--no-optimize"], "");
+    }
+    for (literal, station) in FAILED_CONVERSIONS {
+        let file = NamedTempFile::new("tmp").unwrap();
+        file.write_str(&format!("[start]═─{{{literal}}}═─[{station}]═─[println]"))
+            .unwrap();
+        Command::new(build(file.path(), &["--no-optimize"], &dir))
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains("Can't convert"));
+    }
+}
+
+/// Programs using strings, and what they print
+const STRING_PROGRAMS: [(&str, &str); 14] = [
+    ("[start]═─{\"héllo\"}═─[len]═─[println]", "5\n"),
+    ("[start]═─{2}═─[charat]═─[println]\n   ╚─{\"héllo\"}═┘", "l\n"),
+    ("[start]═─{\"héllo\"}═─[substr]═─[println]\n   ║╚─{3}═───────────┘│\n   └─{1}═─────────────┘", "él\n"),
+    ("[start]═─{\",\"}═─[split]═─[println]\n   ╚─{\"a,b,,c\"}═─┘", "[\"a\", \"b\", \"\", \"c\"]\n"),
+    ("[start]═─{\"\"}═─[split]═─[println]\n   ╚─{\"añb\"}═───┘", "[\"a\", \"ñ\", \"b\"]\n"),
+    ("[start]═─{'-'}═─[join]═─[println]\n   ╚─{[1, \"b\", 'c']}═┘", "1-b-c\n"),
+    ("[start]═─{\"Straße\"}═─[upper]═─[println]", "STRASSE\n"),
+    ("[start]═─{\"ÀB\"}═─[lower]═─[println]", "àb\n"),
+    ("[start]═─{\"  hi  \"}═─[trim]═─[println]", "hi\n"),
+    ("[start]═─{\"lo\"}═─[contains]═─[println]\n   ╚─{\"hello\"}═───┘", "true\n"),
+    ("[start]═─{'h'}═─[startswith]═─[println]\n   ╚─{\"hello\"}═──┘", "true\n"),
+    ("[start]═─{\"lo\"}═─[find]═─[println]\n   ╚─{\"héllo\"}═───┘", "3\n"),
+    ("[start]═─{\"z\"}═─[find]═─[println]\n   ╚─{\"héllo\"}═──┘", "-1\n"),
+    ("[start]═─{\"L\"}═─[replace]═─[println]\n   ║╚─{'l'}═─────┘│\n   └─{\"héllo\"}═───┘", "héLLo\n"),
+];
+
+#[test]
+fn test_strings() {
+    for (src, expected) in STRING_PROGRAMS {
+        let file = NamedTempFile::new("tmp").unwrap();
+        file.write_str(src).unwrap();
+        let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+        cmd.arg(file.path());
+        cmd.assert().success().stdout(expected);
+    }
+    let file = NamedTempFile::new("tmp").unwrap();
+    file.write_str("[start]═─{\"x\"}═─[charat]═─[println]\n   ╚─{5}═────────┘")
+        .unwrap();
+    let mut cmd = Command::cargo_bin(BIN_NAME).unwrap();
+    cmd.arg(file.path());
+    cmd.assert().failure().stdout(predicates::str::contains(
+        "Index 5 is out of range for a string of length 1",
+    ));
+}
+
+#[test]
+fn test_build_strings() {
+    for (src, _) in STRING_PROGRAMS {
+        let file = NamedTempFile::new("tmp").unwrap();
+        file.write_str(src).unwrap();
+        assert_builds_like_interpreter(file.path(), &["--no-optimize"], "");
+    }
+}